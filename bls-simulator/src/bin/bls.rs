@@ -0,0 +1,735 @@
+use std::{
+    env, fs,
+    io::{self, Write},
+    process::ExitCode,
+};
+
+use bls_simulator::*;
+
+const USAGE: &str = "\
+Usage: bls <image.png> [options]
+
+Options:
+  -n, --steps <N>          number of steps to simulate (default 100)
+      --settle <N>         then simulate until the circuit is stable, for at most N
+                           more steps, failing if it oscillates or does not settle
+      --seed <SEED>        seed of the gate delay RNG
+      --timing <MODEL>     ideal, unit, random or rise-fall:<RISE>:<FALL>
+      --engine <ENGINE>    sweep or event
+      --threshold <N>      pixels with any colour channel above N are wires (default 223)
+      --alpha-min <N>      pixels with alpha below N are empty
+      --color <RRGGBB=CLASS>
+                           pixels of colour RRGGBB are CLASS: wire, empty, input,
+                           output, probe or comment; pin and probe wires are
+                           named wN
+      --palette-only       only colours given with --color are wires
+      --pins <FILE>        read named pins from FILE instead of <image.png>.toml
+      --load-snapshot <FILE>
+                           continue from the state saved in FILE, taken on the same image
+      --save-snapshot <FILE>
+                           save the state after simulating to FILE
+      --poke <S:X,Y=V>     set the wire at (X, Y) to V (0 or 1) before step S
+      --poke <S:PIN=V>     set the wire of the named pin PIN to V before step S
+      --poke <S:BUS=V>     set the named bus BUS to the integer V before step S,
+                           negative values are written in two's complement
+      --script <FILE>      read pokes from FILE, one per line, '#' starts a comment
+      --bench <FILE>       run the test bench in FILE instead of simulating, failing
+                           if any check does not hold
+      --truth-table        print the output pins for every combination of the input
+                           pins instead of simulating, failing if any never settles
+                           within --settle steps (default 1000)
+      --minimize           with --truth-table, also print a minimal sum of products
+                           of every output pin
+      --probe <NAME=X,Y>   report the wire at (X, Y) as NAME, output pins are
+                           always reported
+      --trace              print probe states after every step as CSV
+  -o, --output <FILE>      write probe states to FILE instead of stdout
+      --vcd <FILE>         write a waveform of all probes to FILE in VCD format
+      --lint               print extraction diagnostics and exit, failing if there are errors
+      --netlist <FILE>     write the extracted netlist to FILE, as Graphviz if it
+                           ends with .dot, otherwise as JSON
+      --verilog <FILE>     write the netlist to FILE as a structural Verilog module
+      --input-pin <NAME=X,Y>
+                           expose the wire at (X, Y) as Verilog input port NAME,
+                           named pins are always exposed
+      --output-pin <NAME=X,Y>
+                           expose the wire at (X, Y) as Verilog output port NAME
+  -h, --help               print this help";
+
+enum PokeTarget {
+    Pixel(u32, u32),
+    /// A named pin or bus.
+    Name(String),
+}
+
+struct Poke {
+    step: u32,
+    target: PokeTarget,
+    /// `0` or `1` unless the target is a bus.
+    value: i64,
+}
+
+#[derive(Clone)]
+struct NamedPoint {
+    name: String,
+    x: u32,
+    y: u32,
+}
+
+struct Options {
+    image_path: String,
+    steps: u32,
+    settle: Option<u32>,
+    seed: Option<u64>,
+    timing: Option<TimingModel>,
+    engine: Option<Engine>,
+    classifier: Classifier,
+    pins: Option<String>,
+    load_snapshot: Option<String>,
+    save_snapshot: Option<String>,
+    pokes: Vec<Poke>,
+    bench: Option<TestBench>,
+    truth_table: bool,
+    minimize: bool,
+    probes: Vec<NamedPoint>,
+    trace: bool,
+    lint: bool,
+    output: Option<String>,
+    vcd: Option<String>,
+    netlist: Option<String>,
+    verilog: Option<String>,
+    input_pins: Vec<NamedPoint>,
+    output_pins: Vec<NamedPoint>,
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.is_empty() || args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        image_path: String::new(),
+        steps: 100,
+        settle: None,
+        seed: None,
+        timing: None,
+        engine: None,
+        classifier: Classifier::default(),
+        pins: None,
+        load_snapshot: None,
+        save_snapshot: None,
+        pokes: Vec::new(),
+        bench: None,
+        truth_table: false,
+        minimize: false,
+        probes: Vec::new(),
+        trace: false,
+        lint: false,
+        output: None,
+        vcd: None,
+        netlist: None,
+        verilog: None,
+        input_pins: Vec::new(),
+        output_pins: Vec::new(),
+    };
+    let mut image_path = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for {}", arg))
+        };
+
+        match arg.as_str() {
+            "-n" | "--steps" => options.steps = parse_number(value()?)?,
+            "--settle" => options.settle = Some(parse_number(value()?)?),
+            "--seed" => options.seed = Some(parse_number(value()?)?),
+            "--timing" => options.timing = Some(parse_timing(value()?)?),
+            "--engine" => options.engine = Some(parse_engine(value()?)?),
+            "--threshold" => options.classifier.threshold = parse_number(value()?)?,
+            "--alpha-min" => options.classifier.alpha_min = Some(parse_number(value()?)?),
+            "--color" => {
+                let (color, class) = parse_color_class(value()?)?;
+                options.classifier = options.classifier.clone().with_color(color, class);
+            }
+            "--palette-only" => options.classifier.palette_only = true,
+            "--pins" => options.pins = Some(value()?.clone()),
+            "--load-snapshot" => options.load_snapshot = Some(value()?.clone()),
+            "--save-snapshot" => options.save_snapshot = Some(value()?.clone()),
+            "--poke" => options.pokes.push(parse_poke(value()?)?),
+            "--script" => {
+                let path = value()?;
+                let script = fs::read_to_string(path)
+                    .map_err(|err| format!("failed to read {}: {}", path, err))?;
+                options.pokes.extend(parse_script(&script)?);
+            }
+            "--bench" => {
+                let path = value()?;
+                let text = fs::read_to_string(path)
+                    .map_err(|err| format!("failed to read {}: {}", path, err))?;
+                let bench = TestBench::parse(&text).map_err(|err| format!("{}: {}", path, err))?;
+                options.bench = Some(bench);
+            }
+            "--truth-table" => options.truth_table = true,
+            "--minimize" => options.minimize = true,
+            "--probe" => options.probes.push(parse_named_point(value()?)?),
+            "--trace" => options.trace = true,
+            "--lint" => options.lint = true,
+            "-o" | "--output" => options.output = Some(value()?.clone()),
+            "--vcd" => options.vcd = Some(value()?.clone()),
+            "--netlist" => options.netlist = Some(value()?.clone()),
+            "--verilog" => options.verilog = Some(value()?.clone()),
+            "--input-pin" => options.input_pins.push(parse_named_point(value()?)?),
+            "--output-pin" => options.output_pins.push(parse_named_point(value()?)?),
+            _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+            _ if image_path.is_none() => image_path = Some(arg.clone()),
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+    }
+
+    options.image_path = image_path.ok_or("missing image path")?;
+    options.pokes.sort_by_key(|poke| poke.step);
+
+    if let Some(poke) = options
+        .pokes
+        .last()
+        .filter(|poke| poke.step >= options.steps)
+    {
+        return Err(format!(
+            "poke before step {} is never applied, only {} steps are simulated",
+            poke.step, options.steps
+        ));
+    }
+
+    Ok(options)
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid number {:?}", value))
+}
+
+fn parse_coords(value: &str) -> Result<(u32, u32), String> {
+    let (x, y) = value
+        .split_once(',')
+        .ok_or_else(|| format!("invalid coordinates {:?}, expected X,Y", value))?;
+    Ok((parse_number(x)?, parse_number(y)?))
+}
+
+fn parse_timing(value: &str) -> Result<TimingModel, String> {
+    match value.split(':').collect::<Vec<_>>().as_slice() {
+        ["ideal"] => Ok(TimingModel::Ideal),
+        ["unit"] => Ok(TimingModel::Unit),
+        ["random"] => Ok(TimingModel::default()),
+        ["rise-fall", rise, fall] => Ok(TimingModel::RiseFall {
+            rise: parse_number(rise)?,
+            fall: parse_number(fall)?,
+        }),
+        _ => Err(format!("invalid timing model {:?}", value)),
+    }
+}
+
+fn parse_engine(value: &str) -> Result<Engine, String> {
+    match value {
+        "sweep" => Ok(Engine::Sweep),
+        "event" => Ok(Engine::EventDriven),
+        _ => Err(format!("invalid engine {:?}", value)),
+    }
+}
+
+/// Parse `RRGGBB=CLASS`, with an optional leading `#`.
+fn parse_color_class(value: &str) -> Result<(Rgb, PixelClass), String> {
+    let invalid = || format!("invalid colour {:?}, expected RRGGBB=CLASS", value);
+
+    let (color, class) = value.split_once('=').ok_or_else(invalid)?;
+    let color = color.trim().trim_start_matches('#');
+    if color.len() != 6 || !color.is_ascii() {
+        return Err(invalid());
+    }
+
+    let mut rgb = [0u8; 3];
+    for (i, channel) in rgb.iter_mut().enumerate() {
+        *channel = u8::from_str_radix(&color[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+    }
+
+    let class = match class.trim() {
+        "wire" => PixelClass::Wire,
+        "empty" => PixelClass::Empty,
+        "input" => PixelClass::InputPin,
+        "output" => PixelClass::OutputPin,
+        "probe" => PixelClass::Probe,
+        "comment" => PixelClass::Comment,
+        _ => return Err(format!("invalid pixel class {:?}", class)),
+    };
+
+    Ok((rgb, class))
+}
+
+/// Parse `STEP:X,Y=STATE` or `STEP:NAME=VALUE`.
+fn parse_poke(value: &str) -> Result<Poke, String> {
+    let invalid = || format!("invalid poke {:?}, expected STEP:X,Y=0|1", value);
+
+    let (step, rest) = value.split_once(':').ok_or_else(invalid)?;
+    let (target, poke_value) = rest.split_once('=').ok_or_else(invalid)?;
+    let target = if target.contains(',') {
+        let (x, y) = parse_coords(target)?;
+        PokeTarget::Pixel(x, y)
+    } else {
+        PokeTarget::Name(target.trim().to_string())
+    };
+    let poke_value = parse_number(poke_value)?;
+
+    if matches!(target, PokeTarget::Pixel(..)) && !(0..=1).contains(&poke_value) {
+        return Err(invalid());
+    }
+
+    Ok(Poke {
+        step: parse_number(step)?,
+        target,
+        value: poke_value,
+    })
+}
+
+fn parse_script(script: &str) -> Result<Vec<Poke>, String> {
+    script
+        .lines()
+        .enumerate()
+        .filter_map(|(line_no, line)| {
+            let line = line.split('#').next().unwrap_or("").trim();
+            (!line.is_empty())
+                .then(|| parse_poke(line).map_err(|err| format!("line {}: {}", line_no + 1, err)))
+        })
+        .collect()
+}
+
+/// Parse `NAME=X,Y`.
+fn parse_named_point(value: &str) -> Result<NamedPoint, String> {
+    let (name, coords) = value
+        .split_once('=')
+        .ok_or_else(|| format!("invalid value {:?}, expected NAME=X,Y", value))?;
+    let (x, y) = parse_coords(coords)?;
+
+    Ok(NamedPoint {
+        name: name.trim().to_string(),
+        x,
+        y,
+    })
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let mut simulator =
+        Simulator::from_png_file_with_classifier(&options.image_path, options.classifier.clone())
+            .map_err(|err| format!("{}: {}", options.image_path, err))?;
+
+    match &options.pins {
+        Some(path) => {
+            let file = PinFile::load(path).map_err(|err| format!("{}: {}", path, err))?;
+            simulator
+                .load_pins(&file)
+                .map_err(|err| format!("{}: {}", path, err))?;
+        }
+        None => {
+            simulator
+                .load_pin_file_for(&options.image_path)
+                .map_err(|err| {
+                    let path = pin_file_path(&options.image_path);
+                    format!("{}: {}", path.display(), err)
+                })?;
+        }
+    }
+
+    if options.lint {
+        print!("{}", diagnostics_report(&simulator.diagnostics));
+
+        let has_errors = simulator
+            .diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.kind.severity() == Severity::Error);
+        return if has_errors {
+            Err("circuit has extraction errors".to_string())
+        } else {
+            Ok(())
+        };
+    }
+
+    if let Some(seed) = options.seed {
+        simulator = simulator.with_seed(seed);
+    }
+    if let Some(timing) = options.timing {
+        simulator = simulator.with_timing(timing);
+    }
+    if let Some(engine) = options.engine {
+        simulator = simulator.with_engine(engine);
+    }
+
+    if let Some(path) = &options.load_snapshot {
+        Snapshot::load(path)
+            .and_then(|snapshot| simulator.restore(&snapshot))
+            .map_err(|err| format!("{}: {}", path, err))?;
+    }
+
+    if let Some(path) = &options.netlist {
+        write_netlist(&simulator, path)?;
+    }
+
+    if let Some(path) = &options.verilog {
+        write_verilog(&simulator, options, path)?;
+    }
+
+    let mut probes: Vec<NamedPoint> = simulator
+        .markers
+        .iter()
+        .filter(|marker| marker.class == PixelClass::Probe)
+        .map(|marker| NamedPoint {
+            name: format!("w{}", marker.wire_id),
+            x: marker.x,
+            y: marker.y,
+        })
+        .collect();
+    let pins = simulator.pins();
+    let output_pins = pins
+        .pins
+        .iter()
+        .chain(pins.buses.iter().flat_map(|bus| &bus.bits))
+        .filter(|pin| pin.direction == PortDirection::Output);
+    probes.extend(output_pins.map(|pin| NamedPoint {
+        name: pin.name.clone(),
+        x: pin.x,
+        y: pin.y,
+    }));
+    probes.extend(options.probes.iter().cloned());
+
+    for probe in &probes {
+        if !simulator.add_probe(&probe.name, probe.x, probe.y) {
+            return Err(format!(
+                "probe {} at ({}, {}) is not on a wire",
+                probe.name, probe.x, probe.y
+            ));
+        }
+    }
+
+    let mut out: Box<dyn Write> = match &options.output {
+        Some(path) => Box::new(
+            fs::File::create(path).map_err(|err| format!("failed to create {}: {}", path, err))?,
+        ),
+        None => Box::new(io::stdout().lock()),
+    };
+    let write_err = |err: io::Error| format!("failed to write output: {}", err);
+
+    if let Some(bench) = &options.bench {
+        let report = bench
+            .run(&mut simulator)
+            .map_err(|err| format!("test bench: {}", err))?;
+        match &report.failure {
+            None => writeln!(out, "PASS: {} checks", report.passed),
+            Some(failure) => writeln!(
+                out,
+                "FAIL: {} ({} checks passed before)",
+                failure, report.passed
+            ),
+        }
+        .map_err(write_err)?;
+        out.flush().map_err(write_err)?;
+
+        if let Some(path) = &options.vcd {
+            write_vcd(&simulator, path)?;
+        }
+
+        return if report.is_pass() {
+            Ok(())
+        } else {
+            Err("test bench failed".to_string())
+        };
+    }
+
+    if options.truth_table {
+        let inputs = named_pins(&simulator, PortDirection::Input);
+        let outputs = named_pins(&simulator, PortDirection::Output);
+        let inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+        let outputs: Vec<&str> = outputs.iter().map(String::as_str).collect();
+        if inputs.is_empty() || outputs.is_empty() {
+            return Err("truth table needs named input and output pins".to_string());
+        }
+
+        let table = simulator
+            .truth_table(&inputs, &outputs, options.settle.unwrap_or(1000))
+            .map_err(|err| format!("truth table: {}", err))?;
+
+        table.write(&mut out).map_err(write_err)?;
+        if options.minimize {
+            writeln!(out).map_err(write_err)?;
+            for (i, name) in table.outputs.iter().enumerate() {
+                writeln!(out, "{} = {}", name, table.minimized(i)).map_err(write_err)?;
+            }
+        }
+        out.flush().map_err(write_err)?;
+
+        let unsettled = table.unsettled().count();
+        return if unsettled == 0 {
+            Ok(())
+        } else {
+            Err(format!("{} input combinations never settle", unsettled))
+        };
+    }
+
+    if options.trace {
+        let names: Vec<&str> = probes.iter().map(|probe| probe.name.as_str()).collect();
+        writeln!(out, "step,{}", names.join(",")).map_err(write_err)?;
+        write_trace_row(&mut out, &simulator, &probes, 0).map_err(write_err)?;
+    }
+
+    let mut pokes = options.pokes.iter().peekable();
+
+    for step in 0..options.steps {
+        while let Some(poke) = pokes.next_if(|poke| poke.step <= step) {
+            match &poke.target {
+                PokeTarget::Pixel(x, y) => {
+                    if !simulator.set(*x, *y, poke.value == 1) {
+                        eprintln!(
+                            "warning: poke at ({}, {}) before step {} is not on a wire",
+                            x, y, poke.step
+                        );
+                    }
+                }
+                PokeTarget::Name(name) => poke_name(&mut simulator, name, poke)?,
+            }
+        }
+
+        simulator.simulate_one_step();
+
+        if options.trace {
+            write_trace_row(&mut out, &simulator, &probes, step + 1).map_err(write_err)?;
+        }
+    }
+
+    let mut trace_result = Ok(());
+    let stability = options.settle.map(|max_steps| {
+        simulator.simulate_until_stable_with(max_steps, |simulator| {
+            if options.trace && trace_result.is_ok() {
+                let step = simulator.step_count() as u32;
+                trace_result = write_trace_row(&mut out, simulator, &probes, step);
+            }
+        })
+    });
+    trace_result.map_err(write_err)?;
+
+    if let Some(stability) = &stability {
+        eprintln!("{}", describe_stability(&simulator, stability));
+    }
+
+    if !options.trace {
+        for probe in &probes {
+            let state = probe_state(&simulator, probe);
+            writeln!(out, "{}={}", probe.name, state as u8).map_err(write_err)?;
+        }
+    }
+
+    out.flush().map_err(write_err)?;
+
+    if let Some(path) = &options.vcd {
+        write_vcd(&simulator, path)?;
+    }
+
+    if let Some(path) = &options.save_snapshot {
+        simulator
+            .snapshot()
+            .save(path)
+            .map_err(|err| format!("{}: {}", path, err))?;
+    }
+
+    match stability {
+        Some(stability) if !stability.is_stable() => Err("circuit did not settle".to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// Names of the single wire pins going in `direction`.
+fn named_pins(simulator: &Simulator, direction: PortDirection) -> Vec<String> {
+    simulator
+        .pins()
+        .pins
+        .iter()
+        .filter(|pin| pin.direction == direction)
+        .map(|pin| pin.name.clone())
+        .collect()
+}
+
+fn describe_stability(simulator: &Simulator, stability: &Stability) -> String {
+    match stability {
+        Stability::Stable { steps } => format!("stable after {} more steps", steps),
+        Stability::Oscillating {
+            steps,
+            period,
+            wire_ids,
+        } => {
+            let wires: Vec<String> = wire_ids
+                .iter()
+                .map(|&wire_id| {
+                    let pin = simulator
+                        .pins()
+                        .pins
+                        .iter()
+                        .find(|pin| pin.wire_id == wire_id);
+                    match pin {
+                        Some(pin) => format!("w{} ({})", wire_id, pin.name),
+                        None => format!("w{}", wire_id),
+                    }
+                })
+                .collect();
+            format!(
+                "oscillating with period {} after {} more steps: {}",
+                period,
+                steps,
+                wires.join(", ")
+            )
+        }
+        Stability::Unsettled { steps } => format!("not stable after {} more steps", steps),
+    }
+}
+
+fn poke_name(simulator: &mut Simulator, name: &str, poke: &Poke) -> Result<(), String> {
+    let done = if simulator.pins().pin(name).is_some() {
+        (0..=1).contains(&poke.value) && simulator.set_pin(name, poke.value == 1)
+    } else if simulator.pins().bus(name).is_some() {
+        if poke.value < 0 {
+            simulator.set_bus_signed(name, poke.value)
+        } else {
+            simulator.set_bus(name, poke.value as u64)
+        }
+    } else {
+        return Err(format!("poke before step {}: no pin {}", poke.step, name));
+    };
+
+    if done {
+        Ok(())
+    } else {
+        Err(format!(
+            "poke before step {}: {} does not fit in {}",
+            poke.step, poke.value, name
+        ))
+    }
+}
+
+fn write_vcd(simulator: &Simulator, path: &str) -> Result<(), String> {
+    let write_err = |err: io::Error| format!("failed to write {}: {}", path, err);
+
+    let file = fs::File::create(path).map_err(write_err)?;
+    let mut file = io::BufWriter::new(file);
+    simulator.write_vcd(&mut file).map_err(write_err)?;
+
+    file.flush().map_err(write_err)
+}
+
+fn write_netlist(simulator: &Simulator, path: &str) -> Result<(), String> {
+    let netlist = simulator.netlist();
+    let write_err = |err: io::Error| format!("failed to write {}: {}", path, err);
+
+    let file = fs::File::create(path).map_err(write_err)?;
+    let mut file = io::BufWriter::new(file);
+
+    if path.ends_with(".dot") {
+        netlist.write_dot(&mut file).map_err(write_err)?;
+    } else {
+        writeln!(file, "{}", netlist.to_json()).map_err(write_err)?;
+    }
+
+    file.flush().map_err(write_err)
+}
+
+fn write_verilog(simulator: &Simulator, options: &Options, path: &str) -> Result<(), String> {
+    let pins = options
+        .input_pins
+        .iter()
+        .map(|pin| (pin, PortDirection::Input))
+        .chain(
+            options
+                .output_pins
+                .iter()
+                .map(|pin| (pin, PortDirection::Output)),
+        );
+
+    let named_pins = simulator.pins();
+    let mut ports: Vec<VerilogPort> = named_pins
+        .pins
+        .iter()
+        .chain(named_pins.buses.iter().flat_map(|bus| &bus.bits))
+        .map(|pin| VerilogPort {
+            name: pin.name.clone(),
+            wire_id: pin.wire_id,
+            direction: pin.direction,
+        })
+        .collect();
+
+    for (pin, direction) in pins {
+        let wire_id = simulator.wire_id_at(pin.x, pin.y).ok_or_else(|| {
+            format!(
+                "pin {} at ({}, {}) is not on a wire",
+                pin.name, pin.x, pin.y
+            )
+        })?;
+        ports.push(VerilogPort {
+            name: pin.name.clone(),
+            wire_id,
+            direction,
+        });
+    }
+
+    let verilog_options = VerilogOptions {
+        ports,
+        ..Default::default()
+    };
+    let write_err = |err: io::Error| format!("failed to write {}: {}", path, err);
+
+    let file = fs::File::create(path).map_err(write_err)?;
+    let mut file = io::BufWriter::new(file);
+    simulator
+        .netlist()
+        .write_verilog(&mut file, &verilog_options)
+        .map_err(write_err)?;
+
+    file.flush().map_err(write_err)
+}
+
+fn write_trace_row(
+    out: &mut dyn Write,
+    simulator: &Simulator,
+    probes: &[NamedPoint],
+    step: u32,
+) -> io::Result<()> {
+    write!(out, "{}", step)?;
+    for probe in probes {
+        write!(out, ",{}", probe_state(simulator, probe) as u8)?;
+    }
+    writeln!(out)
+}
+
+/// State of the wire under the probe, as pins and buses read it.
+fn probe_state(simulator: &Simulator, probe: &NamedPoint) -> bool {
+    simulator
+        .wire_id_at(probe.x, probe.y)
+        .is_some_and(|wire_id| simulator.get_wire_state(wire_id))
+}
@@ -0,0 +1,75 @@
+use crate::BitOrder;
+
+/// Ordered wires written and read together as one integer of up to 64 bits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WireBus {
+    /// Least significant bit first.
+    wire_ids: Vec<u32>,
+}
+
+impl WireBus {
+    /// Group `wire_ids`, listed in `order`.
+    ///
+    /// Panics if there are more than 64 wires.
+    pub fn new(mut wire_ids: Vec<u32>, order: BitOrder) -> Self {
+        assert!(wire_ids.len() <= 64, "a bus holds at most 64 wires");
+
+        if order == BitOrder::MsbFirst {
+            wire_ids.reverse();
+        }
+
+        Self { wire_ids }
+    }
+
+    /// Wire of every bit, least significant first.
+    pub fn wire_ids(&self) -> &[u32] {
+        &self.wire_ids
+    }
+
+    pub fn width(&self) -> u32 {
+        self.wire_ids.len() as u32
+    }
+
+    /// Whether `value` fits in the bus as an unsigned integer.
+    pub fn fits_unsigned(&self, value: u64) -> bool {
+        self.width() >= 64 || value >> self.width() == 0
+    }
+
+    /// Whether `value` fits in the bus as a two's complement integer.
+    pub fn fits_signed(&self, value: i64) -> bool {
+        match self.width() {
+            0 => value == 0,
+            64.. => true,
+            width => {
+                let limit = 1i64 << (width - 1);
+                (-limit..limit).contains(&value)
+            }
+        }
+    }
+
+    /// State of every wire, least significant first, holding the low bits of `value`.
+    pub(crate) fn encode(&self, value: u64) -> impl Iterator<Item = (u32, bool)> + '_ {
+        self.wire_ids
+            .iter()
+            .enumerate()
+            .map(move |(bit, &wire_id)| (wire_id, value >> bit & 1 == 1))
+    }
+
+    /// Unsigned integer made of the state of every wire, least significant first.
+    pub(crate) fn decode(&self, states: impl Iterator<Item = bool>) -> u64 {
+        states
+            .enumerate()
+            .fold(0, |value, (bit, state)| value | (state as u64) << bit)
+    }
+
+    /// Sign extend an unsigned value read from the bus.
+    pub(crate) fn sign_extend(&self, value: u64) -> i64 {
+        match self.width() {
+            0 => 0,
+            width => {
+                let shift = 64 - width;
+                ((value << shift) as i64) >> shift
+            }
+        }
+    }
+}
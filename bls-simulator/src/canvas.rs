@@ -0,0 +1,335 @@
+use std::{collections::HashMap, io, path::Path};
+
+use crate::{save_png_file, PixelBuffer};
+
+/// An 8-bit RGBA colour.
+pub type Rgba = [u8; 4];
+
+/// A rectangle of pixels, possibly empty.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl PixelRect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// The rectangle spanning both corners, which are included.
+    pub fn from_corners((x0, y0): (u32, u32), (x1, y1): (u32, u32)) -> Self {
+        Self::new(
+            x0.min(x1),
+            y0.min(y1),
+            x0.abs_diff(x1) + 1,
+            y0.abs_diff(y1) + 1,
+        )
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn contains(self, x: u32, y: u32) -> bool {
+        x >= self.x && y >= self.y && x - self.x < self.width && y - self.y < self.height
+    }
+
+    /// The smallest rectangle containing both.
+    pub fn union(self, other: Self) -> Self {
+        if self.is_empty() {
+            return other;
+        }
+        if other.is_empty() {
+            return self;
+        }
+
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+
+        Self::new(x, y, right - x, bottom - y)
+    }
+
+    /// Grow by `margin` pixels on every side, staying inside a `width` by `height` image.
+    pub fn expand(self, margin: u32, width: u32, height: u32) -> Self {
+        if self.is_empty() {
+            return self;
+        }
+
+        let x = self.x.saturating_sub(margin);
+        let y = self.y.saturating_sub(margin);
+        let right = self
+            .x
+            .saturating_add(self.width)
+            .saturating_add(margin)
+            .min(width);
+        let bottom = self
+            .y
+            .saturating_add(self.height)
+            .saturating_add(margin)
+            .min(height);
+
+        Self::new(x, y, right.saturating_sub(x), bottom.saturating_sub(y))
+    }
+
+    /// The part inside a `width` by `height` image.
+    pub fn clip(self, width: u32, height: u32) -> Self {
+        self.expand(0, width, height)
+    }
+}
+
+/// Pixels copied out of a [`Canvas`], to be pasted elsewhere.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Clip {
+    pub width: u32,
+    pub height: u32,
+    /// 8-bit RGBA pixels in row-major order.
+    pub data: Vec<u8>,
+}
+
+/// Pixels changed at once, undone and redone together.
+struct CanvasEdit {
+    /// Index, colour before and colour after of every changed pixel.
+    pixels: Vec<(usize, Rgba, Rgba)>,
+    rect: PixelRect,
+}
+
+/// An 8-bit RGBA image edited with drawing tools, with undo and redo.
+///
+/// Drawing adds to an open stroke, which [`Canvas::commit`] turns into a single
+/// undo step. Every drawing call returns the rectangle it may have changed.
+pub struct Canvas {
+    pixels: PixelBuffer,
+    /// Colour before the stroke of every pixel changed by it.
+    stroke: HashMap<usize, Rgba>,
+    stroke_rect: PixelRect,
+    undo: Vec<CanvasEdit>,
+    redo: Vec<CanvasEdit>,
+}
+
+impl Canvas {
+    pub fn new(pixels: PixelBuffer) -> Self {
+        assert_eq!(pixels.components, 4, "canvas pixels must be 8-bit RGBA");
+
+        Self {
+            pixels,
+            stroke: HashMap::new(),
+            stroke_rect: PixelRect::default(),
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    pub fn pixels(&self) -> &PixelBuffer {
+        &self.pixels
+    }
+
+    pub fn width(&self) -> u32 {
+        self.pixels.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.pixels.height
+    }
+
+    /// Colour of the pixel, if it is inside the image.
+    pub fn get(&self, x: u32, y: u32) -> Option<Rgba> {
+        let index = self.index(x, y)?;
+        let mut color = [0; 4];
+        color.copy_from_slice(&self.pixels.data[index * 4..index * 4 + 4]);
+        Some(color)
+    }
+
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        if x >= self.pixels.width || y >= self.pixels.height {
+            return None;
+        }
+
+        Some(y as usize * self.pixels.width as usize + x as usize)
+    }
+
+    /// Paint a single pixel. Returns false if it is outside the image.
+    pub fn set(&mut self, x: u32, y: u32, color: Rgba) -> bool {
+        let (index, before) = match (self.index(x, y), self.get(x, y)) {
+            (Some(index), Some(before)) => (index, before),
+            _ => return false,
+        };
+
+        self.stroke.entry(index).or_insert(before);
+        self.stroke_rect = self.stroke_rect.union(PixelRect::new(x, y, 1, 1));
+        self.pixels.data[index * 4..index * 4 + 4].copy_from_slice(&color);
+
+        true
+    }
+
+    /// Draw a one pixel wide line between both ends, which are included.
+    pub fn line(&mut self, from: (u32, u32), to: (u32, u32), color: Rgba) -> PixelRect {
+        let (mut x, mut y) = (from.0 as i64, from.1 as i64);
+        let (x1, y1) = (to.0 as i64, to.1 as i64);
+        let dx = (x1 - x).abs();
+        let dy = -(y1 - y).abs();
+        let sx = if x < x1 { 1 } else { -1 };
+        let sy = if y < y1 { 1 } else { -1 };
+        let mut error = dx + dy;
+
+        // Bresenham's line algorithm
+        loop {
+            self.set(x as u32, y as u32, color);
+            if x == x1 && y == y1 {
+                break;
+            }
+
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += sx;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += sy;
+            }
+        }
+
+        PixelRect::from_corners(from, to).clip(self.width(), self.height())
+    }
+
+    /// Paint every pixel of the rectangle.
+    pub fn fill_rect(&mut self, rect: PixelRect, color: Rgba) -> PixelRect {
+        let rect = rect.clip(self.width(), self.height());
+
+        for y in rect.y..rect.y + rect.height {
+            for x in rect.x..rect.x + rect.width {
+                self.set(x, y, color);
+            }
+        }
+
+        rect
+    }
+
+    /// Copy the pixels of the rectangle.
+    pub fn copy(&self, rect: PixelRect) -> Clip {
+        let rect = rect.clip(self.width(), self.height());
+        let mut data = Vec::with_capacity(rect.width as usize * rect.height as usize * 4);
+
+        for y in rect.y..rect.y + rect.height {
+            let start = self.index(rect.x, y).unwrap_or_default() * 4;
+            data.extend_from_slice(&self.pixels.data[start..start + rect.width as usize * 4]);
+        }
+
+        Clip {
+            width: rect.width,
+            height: rect.height,
+            data,
+        }
+    }
+
+    /// Paint `clip` with its top left corner at (x, y). Pixels falling outside the image are dropped.
+    pub fn paste(&mut self, clip: &Clip, x: u32, y: u32) -> PixelRect {
+        for (dy, row) in clip
+            .data
+            .chunks_exact(clip.width.max(1) as usize * 4)
+            .enumerate()
+        {
+            for (dx, pixel) in row.chunks_exact(4).enumerate() {
+                let mut color = [0; 4];
+                color.copy_from_slice(pixel);
+                self.set(
+                    x.saturating_add(dx as u32),
+                    y.saturating_add(dy as u32),
+                    color,
+                );
+            }
+        }
+
+        PixelRect::new(x, y, clip.width, clip.height).clip(self.width(), self.height())
+    }
+
+    /// Make the stroke drawn since the last commit a single undo step.
+    /// Undone edits can no longer be redone.
+    pub fn commit(&mut self) {
+        if self.stroke.is_empty() {
+            return;
+        }
+
+        let mut pixels: Vec<(usize, Rgba, Rgba)> = self
+            .stroke
+            .drain()
+            .map(|(index, before)| {
+                let mut after = [0; 4];
+                after.copy_from_slice(&self.pixels.data[index * 4..index * 4 + 4]);
+                (index, before, after)
+            })
+            .filter(|&(_, before, after)| before != after)
+            .collect();
+        pixels.sort_unstable_by_key(|&(index, _, _)| index);
+
+        let rect = std::mem::take(&mut self.stroke_rect);
+        if !pixels.is_empty() {
+            self.undo.push(CanvasEdit { pixels, rect });
+            self.redo.clear();
+        }
+    }
+
+    /// Throw away the stroke drawn since the last commit, as if it was never drawn.
+    pub fn revert(&mut self) -> PixelRect {
+        for (index, before) in self.stroke.drain() {
+            self.pixels.data[index * 4..index * 4 + 4].copy_from_slice(&before);
+        }
+
+        std::mem::take(&mut self.stroke_rect)
+    }
+
+    /// Undo the last committed stroke, committing the open one first.
+    /// Returns the rectangle it changed, or None if there is nothing to undo.
+    pub fn undo(&mut self) -> Option<PixelRect> {
+        self.commit();
+        let edit = self.undo.pop()?;
+
+        for &(index, before, _) in &edit.pixels {
+            self.pixels.data[index * 4..index * 4 + 4].copy_from_slice(&before);
+        }
+
+        let rect = edit.rect;
+        self.redo.push(edit);
+        Some(rect)
+    }
+
+    /// Redo the last undone stroke.
+    /// Returns the rectangle it changed, or None if there is nothing to redo.
+    pub fn redo(&mut self) -> Option<PixelRect> {
+        self.commit();
+        let edit = self.redo.pop()?;
+
+        for &(index, _, after) in &edit.pixels {
+            self.pixels.data[index * 4..index * 4 + 4].copy_from_slice(&after);
+        }
+
+        let rect = edit.rect;
+        self.undo.push(edit);
+        Some(rect)
+    }
+
+    /// Number of strokes [`Canvas::undo`] can undo.
+    pub fn undo_len(&self) -> usize {
+        self.undo.len() + usize::from(!self.stroke.is_empty())
+    }
+
+    /// Number of strokes [`Canvas::redo`] can redo.
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    /// Write the image to a PNG file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        save_png_file(path, &self.pixels)
+    }
+}
@@ -0,0 +1,119 @@
+use serde::Serialize;
+
+/// An 8-bit RGB colour.
+pub type Rgb = [u8; 3];
+
+/// What a pixel means to the simulator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PixelClass {
+    Empty,
+    Wire,
+    /// Part of a wire, marking it as an input of the circuit.
+    InputPin,
+    /// Part of a wire, marking it as an output of the circuit.
+    OutputPin,
+    /// Part of a wire, marking it as worth watching.
+    Probe,
+    /// Annotation drawn by the artist, never part of a wire.
+    Comment,
+}
+
+impl PixelClass {
+    pub fn is_conductive(self) -> bool {
+        matches!(
+            self,
+            Self::Wire | Self::InputPin | Self::OutputPin | Self::Probe
+        )
+    }
+}
+
+/// Decides which pixels of an image are wires.
+///
+/// By default a pixel is a wire if any colour channel is brighter than 223,
+/// and alpha is ignored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Classifier {
+    /// A pixel whose colour is not in the palette is a wire if any colour channel is above this.
+    pub threshold: u8,
+    /// Colours with a fixed class, taking precedence over the threshold.
+    pub palette: Vec<(Rgb, PixelClass)>,
+    /// Only colours in the palette can be wires, the threshold is not used.
+    pub palette_only: bool,
+    /// Pixels whose alpha is below this are empty, whatever their colour.
+    /// `None` ignores alpha.
+    pub alpha_min: Option<u8>,
+}
+
+impl Default for Classifier {
+    fn default() -> Self {
+        Self {
+            threshold: 223,
+            palette: Vec::new(),
+            palette_only: false,
+            alpha_min: None,
+        }
+    }
+}
+
+impl Classifier {
+    pub fn with_threshold(mut self, threshold: u8) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Give `color` a fixed class. A colour added twice keeps the last class.
+    pub fn with_color(mut self, color: Rgb, class: PixelClass) -> Self {
+        self.palette.retain(|&(other, _)| other != color);
+        self.palette.push((color, class));
+        self
+    }
+
+    pub fn with_palette_only(mut self, palette_only: bool) -> Self {
+        self.palette_only = palette_only;
+        self
+    }
+
+    pub fn with_alpha_min(mut self, alpha_min: Option<u8>) -> Self {
+        self.alpha_min = alpha_min;
+        self
+    }
+
+    /// Classify a pixel made of 8-bit channels where the last channel is alpha.
+    /// A pixel with a single colour channel is gray.
+    pub fn classify(&self, pixel: &[u8]) -> PixelClass {
+        let (&alpha, color) = match pixel.split_last() {
+            Some(split) => split,
+            None => return PixelClass::Empty,
+        };
+
+        if self.alpha_min.is_some_and(|alpha_min| alpha < alpha_min) {
+            return PixelClass::Empty;
+        }
+
+        let rgb = match *color {
+            [gray] => [gray; 3],
+            [r, g, b, ..] => [r, g, b],
+            _ => return PixelClass::Empty,
+        };
+
+        if let Some(&(_, class)) = self.palette.iter().find(|&&(other, _)| other == rgb) {
+            return class;
+        }
+
+        if !self.palette_only && color.iter().any(|&value| value > self.threshold) {
+            PixelClass::Wire
+        } else {
+            PixelClass::Empty
+        }
+    }
+}
+
+/// A wire carrying pixels of an annotation class, located by its first such pixel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Marker {
+    pub class: PixelClass,
+    pub wire_id: u32,
+    pub x: u32,
+    pub y: u32,
+}
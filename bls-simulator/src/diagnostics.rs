@@ -0,0 +1,173 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::{NotGate, Wire};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticKind {
+    /// An empty pixel surrounded by four wires with a single corner filled,
+    /// which is neither a crossing nor a gate, so it is ignored.
+    UnrecognizedPattern,
+    /// A wire that neither is driven by nor drives any gate.
+    FloatingWire,
+    /// A gate whose input and output are the same wire.
+    SelfLoopGate,
+    /// A wire driven by more than one gate, which makes it a wired-OR.
+    ShortedOutputs,
+}
+
+impl DiagnosticKind {
+    pub fn severity(self) -> Severity {
+        match self {
+            Self::UnrecognizedPattern => Severity::Error,
+            Self::FloatingWire | Self::SelfLoopGate => Severity::Warning,
+            Self::ShortedOutputs => Severity::Info,
+        }
+    }
+}
+
+/// Something suspicious found while extracting a circuit, located by pixel coordinates.
+#[derive(Clone, Debug, Serialize)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub x: u32,
+    pub y: u32,
+    pub wire_id: Option<u32>,
+    pub gate_id: Option<u32>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.kind.severity() {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(
+            f,
+            "{}: ({}, {}): {}",
+            severity, self.x, self.y, self.message
+        )
+    }
+}
+
+/// Human readable report, one diagnostic per line followed by a summary.
+pub fn diagnostics_report(diagnostics: &[Diagnostic]) -> String {
+    let mut report = String::new();
+
+    for diagnostic in diagnostics {
+        report.push_str(&diagnostic.to_string());
+        report.push('\n');
+    }
+
+    let count = |severity| {
+        diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.kind.severity() == severity)
+            .count()
+    };
+    report.push_str(&format!(
+        "{} errors, {} warnings, {} infos\n",
+        count(Severity::Error),
+        count(Severity::Warning),
+        count(Severity::Info)
+    ));
+
+    report
+}
+
+/// Lint an extracted circuit.
+/// `unrecognized` holds the centers of the neighbourhoods extraction could not classify.
+pub(crate) fn collect_diagnostics(
+    wire_map: &[Vec<i32>],
+    wires: &[Wire],
+    gates: &[NotGate],
+    unrecognized: &[(u32, u32)],
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for &(x, y) in unrecognized {
+        diagnostics.push(Diagnostic {
+            kind: DiagnosticKind::UnrecognizedPattern,
+            x,
+            y,
+            wire_id: None,
+            gate_id: None,
+            message: "neighbourhood is neither a crossing nor a gate".to_string(),
+        });
+    }
+
+    // Locate each wire by its first pixel in reading order.
+    let mut wire_positions: Vec<Option<(u32, u32)>> = vec![None; wires.len()];
+    for (y, row) in wire_map.iter().enumerate() {
+        for (x, &wire_id) in row.iter().enumerate() {
+            if wire_id != -1 && wire_positions[wire_id as usize].is_none() {
+                wire_positions[wire_id as usize] = Some((x as u32, y as u32));
+            }
+        }
+    }
+
+    for (wire_id, wire) in wires.iter().enumerate() {
+        // Wires without pixels can't be located nor fixed by the user.
+        let (x, y) = match wire_positions[wire_id] {
+            Some(position) => position,
+            None => continue,
+        };
+
+        if wire.gate_in_ids.is_empty() && wire.gate_out_ids.is_empty() {
+            diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::FloatingWire,
+                x,
+                y,
+                wire_id: Some(wire_id as u32),
+                gate_id: None,
+                message: format!("wire {} has no driver and no load", wire_id),
+            });
+        }
+
+        if wire.gate_in_ids.len() > 1 {
+            let gate_ids: Vec<String> = wire.gate_in_ids.iter().map(u32::to_string).collect();
+            diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::ShortedOutputs,
+                x,
+                y,
+                wire_id: Some(wire_id as u32),
+                gate_id: None,
+                message: format!(
+                    "wire {} is driven by gates {}",
+                    wire_id,
+                    gate_ids.join(", ")
+                ),
+            });
+        }
+    }
+
+    for (gate_id, gate) in gates.iter().enumerate() {
+        if gate.wire_in_id == gate.wire_out_id {
+            diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::SelfLoopGate,
+                x: gate.x,
+                y: gate.y,
+                wire_id: Some(gate.wire_in_id as u32),
+                gate_id: Some(gate_id as u32),
+                message: format!(
+                    "gate {} has wire {} as both input and output",
+                    gate_id, gate.wire_in_id
+                ),
+            });
+        }
+    }
+
+    diagnostics
+}
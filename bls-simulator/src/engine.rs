@@ -0,0 +1,105 @@
+/// How [`Simulator`](crate::Simulator) advances a step.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Engine {
+    /// Recompute every wire and every gate on each step.
+    Sweep,
+    /// Only recompute wires whose drivers changed and gates whose input changed
+    /// or which are still ramping towards their new state.
+    /// Identical to [`Engine::Sweep`] under [`TimingModel::Unit`](crate::TimingModel::Unit).
+    ///
+    /// Gates are updated in the order their inputs changed rather than by id, so under
+    /// [`TimingModel::Random`](crate::TimingModel::Random) they draw their delays from the
+    /// RNG in another order, and the same seed gives a different trace than with
+    /// [`Engine::Sweep`]. Select [`Engine::Sweep`] to reproduce runs made before this
+    /// engine became the default.
+    #[default]
+    EventDriven,
+}
+
+/// Pending work of the event driven engine.
+#[derive(Clone, Default)]
+pub struct EventQueue {
+    pending_wires: Vec<u32>,
+    wire_pending: Vec<bool>,
+
+    active_gates: Vec<u32>,
+    gate_active: Vec<bool>,
+}
+
+impl EventQueue {
+    pub fn new(wire_count: usize, gate_count: usize) -> Self {
+        let mut queue = Self {
+            pending_wires: Vec::new(),
+            wire_pending: vec![false; wire_count],
+            active_gates: Vec::new(),
+            gate_active: vec![false; gate_count],
+        };
+        queue.mark_all();
+        queue
+    }
+
+    /// Schedule every wire and gate, e.g. after the state was changed from outside.
+    pub fn mark_all(&mut self) {
+        for wire_id in 0..self.wire_pending.len() {
+            self.mark_wire(wire_id as u32);
+        }
+
+        for gate_id in 0..self.gate_active.len() {
+            self.activate_gate(gate_id as u32);
+        }
+    }
+
+    /// Schedule the wire to be recomputed on the next step.
+    pub fn mark_wire(&mut self, wire_id: u32) {
+        if !self.wire_pending[wire_id as usize] {
+            self.wire_pending[wire_id as usize] = true;
+            self.pending_wires.push(wire_id);
+        }
+    }
+
+    /// Schedule the gate to be updated on the next step.
+    pub fn activate_gate(&mut self, gate_id: u32) {
+        if !self.gate_active[gate_id as usize] {
+            self.gate_active[gate_id as usize] = true;
+            self.active_gates.push(gate_id);
+        }
+    }
+
+    /// Take the wires to recompute.
+    pub fn take_wires(&mut self) -> Vec<u32> {
+        let wire_ids = std::mem::take(&mut self.pending_wires);
+        for &wire_id in &wire_ids {
+            self.wire_pending[wire_id as usize] = false;
+        }
+        wire_ids
+    }
+
+    /// Take the gates to update.
+    pub fn take_gates(&mut self) -> Vec<u32> {
+        let gate_ids = std::mem::take(&mut self.active_gates);
+        for &gate_id in &gate_ids {
+            self.gate_active[gate_id as usize] = false;
+        }
+        gate_ids
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending_wires.is_empty() && self.active_gates.is_empty()
+    }
+
+    /// Wires to recompute, in the order they will be.
+    pub fn pending_wires(&self) -> &[u32] {
+        &self.pending_wires
+    }
+
+    /// Gates to update, in the order they will be.
+    pub fn active_gates(&self) -> &[u32] {
+        &self.active_gates
+    }
+
+    /// Forget the pending work.
+    pub fn clear(&mut self) {
+        self.take_wires();
+        self.take_gates();
+    }
+}
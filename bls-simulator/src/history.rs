@@ -0,0 +1,322 @@
+use std::{collections::VecDeque, mem, ops::RangeInclusive};
+
+use rand_pcg::Pcg32;
+
+use crate::{EventQueue, GateSnapshot, Simulator};
+
+/// Changes made by one step, enough to undo and redo it.
+///
+/// Wires changed from outside before the step are part of it.
+#[derive(Clone)]
+struct StepDelta {
+    /// Wires whose state changed, with their state after the step.
+    wires: Vec<(u32, bool)>,
+    /// Gates whose state changed, with their state before and after the step.
+    gates: Vec<(u32, GateSnapshot, GateSnapshot)>,
+    /// RNG after the step. The RNG before it is the one after the previous step.
+    rng_after: Pcg32,
+}
+
+/// The last steps of a [`Simulator`], kept to step back and forward through them
+/// without simulating again.
+#[derive(Clone)]
+pub struct History {
+    capacity: usize,
+    /// Steps that can be undone, oldest first.
+    past: VecDeque<StepDelta>,
+    /// Undone steps that can be redone, the next one last.
+    future: Vec<StepDelta>,
+
+    /// State the next step is compared against.
+    wires: Vec<bool>,
+    gates: Vec<GateSnapshot>,
+    /// RNG before the oldest step that can be undone.
+    rng_base: Pcg32,
+
+    /// Wires and gates the engine looked at since the last step was recorded.
+    /// The others cannot differ from the compared state, unless all were looked at.
+    touched_wires: Vec<u32>,
+    touched_gates: Vec<u32>,
+    touched_all: bool,
+
+    /// Pending events of the newest step, put back once every undone step is redone.
+    newest_events: Option<EventQueue>,
+}
+
+impl History {
+    fn new(capacity: usize, simulator: &Simulator) -> Self {
+        let mut history = Self {
+            capacity,
+            past: VecDeque::new(),
+            future: Vec::new(),
+            wires: Vec::new(),
+            gates: Vec::new(),
+            rng_base: simulator.rng.clone(),
+            touched_wires: Vec::new(),
+            touched_gates: Vec::new(),
+            touched_all: false,
+            newest_events: None,
+        };
+        history.rebase(simulator);
+        history
+    }
+
+    /// Number of steps kept at most.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of steps that can be undone.
+    pub fn undo_len(&self) -> usize {
+        self.past.len()
+    }
+
+    /// Number of undone steps that can be redone.
+    pub fn redo_len(&self) -> usize {
+        self.future.len()
+    }
+
+    /// Forget every step and compare the next one against the current state.
+    fn rebase(&mut self, simulator: &Simulator) {
+        self.past.clear();
+        self.future.clear();
+        self.newest_events = None;
+        self.wires = simulator.wires.iter().map(|wire| wire.state).collect();
+        self.gates = simulator.gates.iter().map(GateSnapshot::of).collect();
+        self.rng_base = simulator.rng.clone();
+        self.touched_wires.clear();
+        self.touched_gates.clear();
+        self.touched_all = false;
+    }
+
+    /// Forget the undone steps, the circuit takes another course from here.
+    fn branch(&mut self) {
+        self.future.clear();
+        self.newest_events = None;
+    }
+
+    /// Note the wires and gates the engine looked at in this step.
+    pub(crate) fn touch(&mut self, wire_ids: &[u32], gate_ids: &[u32]) {
+        if !self.touched_all {
+            self.touched_wires.extend_from_slice(wire_ids);
+            self.touched_gates.extend_from_slice(gate_ids);
+        }
+    }
+
+    /// Note that the engine looked at every wire and gate in this step.
+    pub(crate) fn touch_all(&mut self) {
+        self.touched_wires.clear();
+        self.touched_gates.clear();
+        self.touched_all = true;
+    }
+
+    /// Compare what the step touched against the previous step and keep the differences.
+    fn record(&mut self, simulator: &Simulator) {
+        let mut delta = StepDelta {
+            wires: Vec::new(),
+            gates: Vec::new(),
+            rng_after: simulator.rng.clone(),
+        };
+
+        let mut touched_wires = mem::take(&mut self.touched_wires);
+        let mut touched_gates = mem::take(&mut self.touched_gates);
+        if mem::take(&mut self.touched_all) {
+            touched_wires = (0..simulator.wires.len() as u32).collect();
+            touched_gates = (0..simulator.gates.len() as u32).collect();
+        }
+
+        // A wire or gate touched twice only differs the first time it is compared.
+        for &wire_id in &touched_wires {
+            let state = &mut self.wires[wire_id as usize];
+            let after = simulator.wires[wire_id as usize].state;
+            if *state != after {
+                *state = after;
+                delta.wires.push((wire_id, after));
+            }
+        }
+        for &gate_id in &touched_gates {
+            let state = &mut self.gates[gate_id as usize];
+            let after = GateSnapshot::of(&simulator.gates[gate_id as usize]);
+            if *state != after {
+                delta.gates.push((gate_id, *state, after));
+                *state = after;
+            }
+        }
+
+        touched_wires.clear();
+        touched_gates.clear();
+        self.touched_wires = touched_wires;
+        self.touched_gates = touched_gates;
+
+        self.past.push_back(delta);
+        if self.past.len() > self.capacity {
+            if let Some(oldest) = self.past.pop_front() {
+                self.rng_base = oldest.rng_after;
+            }
+        }
+    }
+}
+
+impl Simulator {
+    /// Keep the changes of the last `capacity` steps, so they can be stepped through
+    /// with [`Simulator::step_back`] and [`Simulator::step_forward`].
+    pub fn with_history(mut self, capacity: usize) -> Self {
+        self.history = Some(History::new(capacity, &self));
+        self
+    }
+
+    pub fn history(&self) -> Option<&History> {
+        self.history.as_ref()
+    }
+
+    /// Steps that can be reached by stepping back and forward, if history is kept.
+    pub fn history_range(&self) -> Option<RangeInclusive<u64>> {
+        let history = self.history.as_ref()?;
+
+        Some(
+            self.step_count - history.undo_len() as u64
+                ..=self.step_count + history.redo_len() as u64,
+        )
+    }
+
+    /// Whether steps were undone, so the current state is not the newest one.
+    pub fn is_rewound(&self) -> bool {
+        self.history
+            .as_ref()
+            .is_some_and(|history| history.redo_len() > 0)
+    }
+
+    /// Undo the last step, along with the wires set since then.
+    /// If there is no step to undo, returns false.
+    /// Otherwise returns true.
+    ///
+    /// The waveform recorder is left as it is, until the circuit takes another course.
+    pub fn step_back(&mut self) -> bool {
+        let history = match self.history.as_mut() {
+            Some(history) => history,
+            None => return false,
+        };
+        let delta = match history.past.pop_back() {
+            Some(delta) => delta,
+            None => return false,
+        };
+
+        if history.future.is_empty() {
+            history.newest_events = Some(self.events.clone());
+
+            // Wires set since the last step go back as well.
+            for (wire, &state) in self.wires.iter_mut().zip(&history.wires) {
+                wire.state = state;
+            }
+        }
+
+        for &(wire_id, state) in &delta.wires {
+            self.wires[wire_id as usize].state = !state;
+            history.wires[wire_id as usize] = !state;
+        }
+        for &(gate_id, before, _) in &delta.gates {
+            let gate = &mut self.gates[gate_id as usize];
+            gate.state = before.state;
+            gate.slow_state = before.slow_state;
+            history.gates[gate_id as usize] = before;
+        }
+
+        self.rng = match history.past.back() {
+            Some(previous) => previous.rng_after.clone(),
+            None => history.rng_base.clone(),
+        };
+        self.step_count -= 1;
+        history.future.push(delta);
+        self.events.mark_all();
+
+        true
+    }
+
+    /// Redo the last undone step, without simulating it again.
+    /// If there is no step to redo, returns false.
+    /// Otherwise returns true.
+    pub fn step_forward(&mut self) -> bool {
+        let history = match self.history.as_mut() {
+            Some(history) => history,
+            None => return false,
+        };
+        let delta = match history.future.pop() {
+            Some(delta) => delta,
+            None => return false,
+        };
+
+        for &(wire_id, state) in &delta.wires {
+            self.wires[wire_id as usize].state = state;
+            history.wires[wire_id as usize] = state;
+        }
+        for &(gate_id, _, after) in &delta.gates {
+            let gate = &mut self.gates[gate_id as usize];
+            gate.state = after.state;
+            gate.slow_state = after.slow_state;
+            history.gates[gate_id as usize] = after;
+        }
+
+        self.rng = delta.rng_after.clone();
+        self.step_count += 1;
+        history.past.push_back(delta);
+
+        match history.newest_events.take() {
+            Some(events) if history.future.is_empty() => self.events = events,
+            newest_events => {
+                history.newest_events = newest_events;
+                self.events.mark_all();
+            }
+        }
+
+        true
+    }
+
+    /// Step back or forward until `step`.
+    /// If `step` is outside of [`Simulator::history_range`], returns false and stays.
+    /// Otherwise returns true.
+    pub fn seek(&mut self, step: u64) -> bool {
+        match self.history_range() {
+            Some(range) if range.contains(&step) => {}
+            _ => return false,
+        }
+
+        while self.step_count > step {
+            self.step_back();
+        }
+        while self.step_count < step {
+            self.step_forward();
+        }
+
+        true
+    }
+
+    /// Record the step just simulated, see [`Simulator::with_history`].
+    pub(crate) fn record_history(&mut self) {
+        if let Some(mut history) = self.history.take() {
+            history.record(self);
+            self.history = Some(history);
+        }
+    }
+
+    /// Forget the undone steps, and what the waveform recorder saw in them, before a wire
+    /// is changed from outside or the next step is simulated.
+    pub(crate) fn branch_history(&mut self) {
+        if let Some(mut history) = self.history.take() {
+            if !history.future.is_empty() {
+                let mut recorder = std::mem::take(&mut self.recorder);
+                recorder.truncate(self.step_count, |wire_id| self.get_wire_state(wire_id));
+                self.recorder = recorder;
+            }
+            history.branch();
+            self.history = Some(history);
+        }
+    }
+
+    /// Forget every step after the whole state was replaced.
+    pub(crate) fn reset_history(&mut self) {
+        if let Some(mut history) = self.history.take() {
+            history.rebase(self);
+            self.history = Some(history);
+        }
+    }
+}
@@ -0,0 +1,796 @@
+//! Headless bitmap logic simulator.
+//!
+//! Circuits are extracted from a plain pixel buffer, so this crate can be used
+//! without pulling in any rendering engine.
+
+mod bus;
+mod canvas;
+mod conductive;
+mod diagnostics;
+mod engine;
+mod history;
+mod loader;
+mod netlist;
+mod not_gate;
+mod pins;
+mod reextract;
+mod reload;
+mod snapshot;
+mod stability;
+mod testbench;
+mod timing;
+mod truth_table;
+mod union_find;
+mod vcd;
+mod verilog;
+mod wire;
+
+use std::{collections::HashSet, io, path::Path};
+
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+
+pub use bus::*;
+pub use canvas::*;
+pub use conductive::*;
+pub use diagnostics::*;
+pub use engine::*;
+pub use history::*;
+pub use loader::*;
+pub use netlist::*;
+pub use not_gate::*;
+pub use pins::*;
+pub use reload::*;
+pub use snapshot::*;
+pub use stability::*;
+pub use testbench::*;
+pub use timing::*;
+pub use truth_table::*;
+pub use union_find::*;
+pub use vcd::*;
+pub use verilog::*;
+pub use wire::*;
+
+pub struct Simulator {
+    pub width: u32,
+    pub height: u32,
+
+    pub wire_map: Vec<Vec<i32>>,
+
+    pub gates: Vec<NotGate>,
+    pub wires: Vec<Wire>,
+
+    /// Problems found while extracting the circuit.
+    pub diagnostics: Vec<Diagnostic>,
+    /// Wires annotated as pins or probes by the colour of their pixels.
+    pub markers: Vec<Marker>,
+
+    /// How pixels were told apart when extracting the circuit.
+    classifier: Classifier,
+    /// [`image_hash`] of the pixels the circuit was extracted from.
+    image_hash: u64,
+
+    /// How fast gates follow their inputs.
+    timing: TimingModel,
+
+    /// How steps are computed.
+    engine: Engine,
+    /// Wires and gates the event driven engine has to look at on the next step.
+    events: EventQueue,
+
+    /// Seed the gate delay RNG was created from.
+    seed: u64,
+    /// Source of randomness for gate delays, so runs can be reproduced.
+    rng: Pcg32,
+
+    /// Number of steps simulated so far.
+    step_count: u64,
+    /// Waveform of the probed wires.
+    recorder: VcdRecorder,
+    /// Changes of the last steps, if kept.
+    history: Option<History>,
+
+    /// Named pins and buses.
+    pins: Pins,
+}
+
+impl Simulator {
+    /// Extract a circuit from a raw pixel buffer.
+    ///
+    /// `data` holds `width * height` pixels in row-major order, each made of
+    /// `components` 8-bit channels where the last channel is alpha.
+    ///
+    /// The gate delay RNG gets a random seed; use [`Simulator::with_seed`] to
+    /// make the run reproducible.
+    pub fn from_pixels(width: u32, height: u32, components: u32, data: &[u8]) -> Self {
+        Self::from_pixels_with_classifier(width, height, components, data, Classifier::default())
+    }
+
+    /// Extract a circuit from a raw pixel buffer, telling wires apart with `classifier`.
+    pub fn from_pixels_with_classifier(
+        width: u32,
+        height: u32,
+        components: u32,
+        data: &[u8],
+        classifier: Classifier,
+    ) -> Self {
+        let components = components as usize;
+        assert_eq!(
+            data.len(),
+            width as usize * height as usize * components,
+            "pixel buffer does not match the image size"
+        );
+
+        let class_map: Vec<Vec<PixelClass>> = data
+            .chunks_exact(components)
+            .map(|pixel| classifier.classify(pixel))
+            .collect::<Vec<_>>()
+            .chunks(width.max(1) as usize)
+            .map(<[PixelClass]>::to_vec)
+            .collect();
+
+        // Find wires horizontally
+        let mut wire_map = vec![vec![-1i32; width as usize]; height as usize];
+        let mut wire_last_id: i32 = -1;
+
+        for (row, classes) in wire_map.iter_mut().zip(&class_map) {
+            // wires never continue onto the next row
+            let mut prev_is_conductive = false;
+
+            for (cell, class) in row.iter_mut().zip(classes) {
+                let cur_is_conductive = class.is_conductive();
+
+                if cur_is_conductive {
+                    if !prev_is_conductive {
+                        wire_last_id += 1;
+                    }
+
+                    *cell = wire_last_id;
+                }
+
+                prev_is_conductive = cur_is_conductive;
+            }
+        }
+
+        // Merge wires vertically using union-find
+        let mut wire_merge = vec![-1i32; (wire_last_id + 1) as usize];
+
+        for y in 1..height as usize {
+            for (&prev_wire_id, &cur_wire_id) in wire_map[y - 1].iter().zip(&wire_map[y]) {
+                if prev_wire_id == -1 || cur_wire_id == -1 {
+                    continue;
+                }
+
+                wire_merge.merge(prev_wire_id, cur_wire_id);
+            }
+        }
+
+        // find crossing wires and not gates
+        let mut gates: Vec<NotGate> = Vec::new();
+        let mut unrecognized: Vec<(u32, u32)> = Vec::new();
+
+        for y in 1..(height as usize).saturating_sub(1) {
+            for x in 1..(width as usize).saturating_sub(1) {
+                match neighbourhood(&wire_map, x, y) {
+                    Some(Neighbourhood::Crossing) => {
+                        wire_merge.merge(wire_map[y][x - 1], wire_map[y][x + 1]);
+                        wire_merge.merge(wire_map[y - 1][x], wire_map[y + 1][x]);
+                    }
+                    Some(Neighbourhood::Gate(direction)) => {
+                        let ((in_x, in_y), (out_x, out_y)) = gate_ends(x, y, direction);
+                        gates.push(NotGate::new(
+                            wire_merge.find(wire_map[in_y][in_x]),
+                            wire_merge.find(wire_map[out_y][out_x]),
+                            x as u32,
+                            y as u32,
+                            direction,
+                        ));
+                    }
+                    Some(Neighbourhood::Unrecognized) => unrecognized.push((x as u32, y as u32)),
+                    None => {}
+                }
+            }
+        }
+
+        // compress wire id
+        let mut wire_remap = vec![0i32; (wire_last_id + 1) as usize];
+        let mut wires: Vec<Wire> = Vec::new();
+
+        for wire_id in 0..(wire_last_id + 1) {
+            if !wire_merge.is_root(wire_id) {
+                continue;
+            }
+
+            wire_remap[wire_id as usize] = wires.len() as i32;
+            wires.push(Wire::new());
+        }
+
+        for row in wire_map.iter_mut() {
+            for cell in row.iter_mut() {
+                let wire_id = wire_merge.find(*cell);
+                if wire_id == -1 {
+                    continue;
+                }
+
+                *cell = wire_remap[wire_id as usize];
+            }
+        }
+
+        for gate in &mut gates {
+            gate.wire_in_id = wire_remap[wire_merge.find(gate.wire_in_id) as usize];
+            gate.wire_out_id = wire_remap[wire_merge.find(gate.wire_out_id) as usize];
+        }
+
+        // connect gates
+        for (gate_id, gate) in gates.iter().enumerate() {
+            wires[gate.wire_out_id as usize]
+                .gate_in_ids
+                .push(gate_id as u32);
+            wires[gate.wire_in_id as usize]
+                .gate_out_ids
+                .push(gate_id as u32);
+        }
+
+        let diagnostics = collect_diagnostics(&wire_map, &wires, &gates, &unrecognized);
+        let markers = collect_markers(&wire_map, &class_map);
+
+        let image_hash = image_hash(width, height, components as u32, data);
+        let seed = rand::random::<u64>();
+        let events = EventQueue::new(wires.len(), gates.len());
+        let mut pins = Pins::default();
+        pins.add_markers(&markers);
+
+        Self {
+            width,
+            height,
+            wire_map,
+            gates,
+            wires,
+            diagnostics,
+            markers,
+            classifier,
+            image_hash,
+            timing: TimingModel::default(),
+            engine: Engine::default(),
+            events,
+            seed,
+            rng: Pcg32::seed_from_u64(seed),
+            step_count: 0,
+            recorder: VcdRecorder::new(),
+            history: None,
+            pins,
+        }
+    }
+
+    /// Load a circuit from a PNG file.
+    pub fn from_png_file<P: AsRef<Path>>(path: P) -> Result<Self, LoadError> {
+        Self::from_png_file_with_classifier(path, Classifier::default())
+    }
+
+    /// Load a circuit from a PNG file, telling wires apart with `classifier`.
+    pub fn from_png_file_with_classifier<P: AsRef<Path>>(
+        path: P,
+        classifier: Classifier,
+    ) -> Result<Self, LoadError> {
+        let pixels = load_png_file(path)?;
+
+        Ok(Self::from_pixels_with_classifier(
+            pixels.width,
+            pixels.height,
+            pixels.components,
+            &pixels.data,
+            classifier,
+        ))
+    }
+
+    pub fn classifier(&self) -> &Classifier {
+        &self.classifier
+    }
+
+    /// Hash of the pixels the circuit was extracted from, see [`image_hash`].
+    pub fn image_hash(&self) -> u64 {
+        self.image_hash
+    }
+
+    /// Reseed the gate delay RNG.
+    /// The same image, seed and input sequence always yields the same wire states.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self.rng = Pcg32::seed_from_u64(seed);
+        self.reset_history();
+        self
+    }
+
+    /// Seed the gate delay RNG was created from.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Select the timing model used for gate propagation.
+    pub fn with_timing(mut self, timing: TimingModel) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    pub fn timing(&self) -> TimingModel {
+        self.timing
+    }
+
+    /// Select the engine used to compute steps.
+    pub fn with_engine(mut self, engine: Engine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    pub fn engine(&self) -> Engine {
+        self.engine
+    }
+
+    /// Make the next step look at every wire and gate.
+    /// Call this after changing `wires` or `gates` directly.
+    pub fn invalidate(&mut self) {
+        self.events.mark_all();
+    }
+
+    /// Id of the wire the coordinates points to.
+    /// If there is no wire the coordinates points to, returns None.
+    pub fn wire_id_at(&self, x: u32, y: u32) -> Option<u32> {
+        let wire_id = *self.wire_map.get(y as usize)?.get(x as usize)?;
+
+        if wire_id == -1 {
+            return None;
+        }
+
+        Some(wire_id as u32)
+    }
+
+    /// Set state of the wire the coordinates points to.
+    /// If there is no wire the coordinates points to, returns false.
+    /// Otherwise returns true.
+    pub fn set(&mut self, x: u32, y: u32, state: bool) -> bool {
+        let wire_id = match self.wire_id_at(x, y) {
+            Some(wire_id) => wire_id,
+            None => return false,
+        };
+
+        self.set_wire(wire_id, state);
+
+        true
+    }
+
+    /// Set state of the wire, see [`Simulator::set`].
+    /// Undone steps can no longer be redone, see [`Simulator::step_back`].
+    pub fn set_wire(&mut self, wire_id: u32, state: bool) {
+        self.branch_history();
+        self.wires[wire_id as usize].state = state;
+        self.events.mark_wire(wire_id);
+    }
+
+    /// Get state of the wire the coordinates points to.
+    /// If there is no wire the coordinates points to, returns None.
+    pub fn get(&self, x: u32, y: u32) -> Option<bool> {
+        let wire_id = self.wire_id_at(x, y)?;
+
+        Some(self.wires[wire_id as usize].state)
+    }
+
+    /// Name pins and buses of the circuit as described by `file`, followed by the pins
+    /// drawn with pin colours. Replaces the pins loaded before.
+    pub fn load_pins(&mut self, file: &PinFile) -> Result<(), PinError> {
+        let mut pins = Pins::resolve(file, self)?;
+        pins.add_markers(&self.markers);
+        self.pins = pins;
+        Ok(())
+    }
+
+    /// Load the pin file next to the image at `image_path`, if there is one.
+    /// Returns whether a pin file was found.
+    pub fn load_pin_file_for<P: AsRef<Path>>(&mut self, image_path: P) -> Result<bool, PinError> {
+        let path = pin_file_path(image_path);
+        if !path.exists() {
+            return Ok(false);
+        }
+
+        self.load_pins(&PinFile::load(path)?)?;
+        Ok(true)
+    }
+
+    pub fn pins(&self) -> &Pins {
+        &self.pins
+    }
+
+    /// Set state of the wire of the pin named `name`.
+    /// If there is no such pin, returns false.
+    /// Otherwise returns true.
+    pub fn set_pin(&mut self, name: &str, state: bool) -> bool {
+        let wire_id = match self.pins.pin(name) {
+            Some(pin) => pin.wire_id,
+            None => return false,
+        };
+
+        self.set_wire(wire_id, state);
+
+        true
+    }
+
+    /// Get state of the wire of the pin named `name`, see [`Simulator::get_wire_state`].
+    /// If there is no such pin, returns None.
+    pub fn get_pin(&self, name: &str) -> Option<bool> {
+        let pin = self.pins.pin(name)?;
+
+        Some(self.get_wire_state(pin.wire_id))
+    }
+
+    /// Set the wires of the bus named `name`, least significant bit first.
+    /// If there is no such bus or `bits` does not match its width, returns false.
+    /// Otherwise returns true.
+    pub fn set_bus_bits(&mut self, name: &str, bits: &[bool]) -> bool {
+        let wire_ids: Vec<u32> = match self.pins.bus(name) {
+            Some(bus) if bus.bits.len() == bits.len() => {
+                bus.bits.iter().map(|bit| bit.wire_id).collect()
+            }
+            _ => return false,
+        };
+
+        for (wire_id, &state) in wire_ids.into_iter().zip(bits) {
+            self.set_wire(wire_id, state);
+        }
+
+        true
+    }
+
+    /// Get state of the wires of the bus named `name`, least significant bit first.
+    /// If there is no such bus, returns None.
+    pub fn get_bus_bits(&self, name: &str) -> Option<Vec<bool>> {
+        let bus = self.pins.bus(name)?;
+
+        Some(
+            bus.bits
+                .iter()
+                .map(|bit| self.get_wire_state(bit.wire_id))
+                .collect(),
+        )
+    }
+
+    /// Set the wires of the bus named `name` to the unsigned `value`.
+    /// If there is no such bus or `value` does not fit in it, returns false.
+    /// Otherwise returns true.
+    pub fn set_bus(&mut self, name: &str, value: u64) -> bool {
+        match self.pins.bus(name) {
+            Some(bus) => self.write_bus(&bus.wire_bus(), value),
+            None => false,
+        }
+    }
+
+    /// Set the wires of the bus named `name` to the two's complement `value`.
+    /// If there is no such bus or `value` does not fit in it, returns false.
+    /// Otherwise returns true.
+    pub fn set_bus_signed(&mut self, name: &str, value: i64) -> bool {
+        match self.pins.bus(name) {
+            Some(bus) => self.write_bus_signed(&bus.wire_bus(), value),
+            None => false,
+        }
+    }
+
+    /// Read the bus named `name` as an unsigned integer.
+    /// If there is no such bus, returns None.
+    pub fn get_bus(&self, name: &str) -> Option<u64> {
+        Some(self.read_bus(&self.pins.bus(name)?.wire_bus()))
+    }
+
+    /// Read the bus named `name` as a two's complement integer.
+    /// If there is no such bus, returns None.
+    pub fn get_bus_signed(&self, name: &str) -> Option<i64> {
+        Some(self.read_bus_signed(&self.pins.bus(name)?.wire_bus()))
+    }
+
+    /// Set every wire of `bus` to its bit of the unsigned `value` before the next step.
+    /// If `value` does not fit in the bus, returns false and leaves the wires untouched.
+    /// Otherwise returns true.
+    pub fn write_bus(&mut self, bus: &WireBus, value: u64) -> bool {
+        if !bus.fits_unsigned(value) {
+            return false;
+        }
+
+        for (wire_id, state) in bus.encode(value) {
+            self.set_wire(wire_id, state);
+        }
+
+        true
+    }
+
+    /// Set every wire of `bus` to its bit of the two's complement `value` before the next step.
+    /// If `value` does not fit in the bus, returns false and leaves the wires untouched.
+    /// Otherwise returns true.
+    pub fn write_bus_signed(&mut self, bus: &WireBus, value: i64) -> bool {
+        if !bus.fits_signed(value) {
+            return false;
+        }
+
+        for (wire_id, state) in bus.encode(value as u64) {
+            self.set_wire(wire_id, state);
+        }
+
+        true
+    }
+
+    /// Read the wires of `bus` as an unsigned integer, see [`Simulator::get_wire_state`].
+    pub fn read_bus(&self, bus: &WireBus) -> u64 {
+        bus.decode(
+            bus.wire_ids()
+                .iter()
+                .map(|&wire_id| self.get_wire_state(wire_id)),
+        )
+    }
+
+    /// Read the wires of `bus` as a two's complement integer, see [`Simulator::get_wire_state`].
+    pub fn read_bus_signed(&self, bus: &WireBus) -> i64 {
+        bus.sign_extend(self.read_bus(bus))
+    }
+
+    /// Extract the netlist of the circuit for export.
+    pub fn netlist(&self) -> Netlist {
+        Netlist::from_simulator(self)
+    }
+
+    /// Number of steps simulated so far.
+    pub fn step_count(&self) -> u64 {
+        self.step_count
+    }
+
+    /// Record the state of the wire the coordinates points to on every step, under `name`.
+    /// If there is no wire the coordinates points to, returns false.
+    /// Otherwise returns true.
+    pub fn add_probe(&mut self, name: &str, x: u32, y: u32) -> bool {
+        let wire_id = match self.wire_id_at(x, y) {
+            Some(wire_id) => wire_id,
+            None => return false,
+        };
+
+        let probe = WireProbe {
+            name: name.to_string(),
+            x,
+            y,
+            wire_id,
+        };
+        let mut recorder = std::mem::take(&mut self.recorder);
+        recorder.add_probe(probe, self.step_count, |wire_id| {
+            self.get_wire_state(wire_id)
+        });
+        self.recorder = recorder;
+
+        true
+    }
+
+    pub fn recorder(&self) -> &VcdRecorder {
+        &self.recorder
+    }
+
+    /// Write the recorded waveform of all probes as a Value Change Dump.
+    pub fn write_vcd<W: io::Write>(&self, out: &mut W) -> io::Result<()> {
+        self.recorder.write(out, self.step_count)
+    }
+
+    /// Get state of the wire.
+    /// If there are some gates connected to the wire, the state of the wire is ON if at least one of the gates is ON.
+    /// If there are no gates connected to the wire, the state of the wire can be set by user interaction.
+    pub fn get_wire_state(&self, wire_id: u32) -> bool {
+        let wire = &self.wires[wire_id as usize];
+
+        if wire.gate_in_ids.is_empty() {
+            wire.state
+        } else {
+            wire.gate_in_ids
+                .iter()
+                .any(|&gate_id| self.gates[gate_id as usize].state)
+        }
+    }
+
+    pub fn simulate_one_step(&mut self) {
+        self.branch_history();
+
+        if self.timing == TimingModel::Ideal {
+            // Any path without feedback settles after passing through every gate once.
+            for _ in 0..=self.gates.len() {
+                if !self.propagate() {
+                    break;
+                }
+            }
+        } else {
+            self.propagate();
+        }
+
+        self.step_count += 1;
+        let mut recorder = std::mem::take(&mut self.recorder);
+        recorder.sample(self.step_count, |wire_id| self.get_wire_state(wire_id));
+        self.recorder = recorder;
+        self.record_history();
+    }
+
+    /// Run a single pass of the selected engine.
+    /// Returns true if any gate output changed.
+    fn propagate(&mut self) -> bool {
+        match self.engine {
+            Engine::Sweep => {
+                let changed = self.sweep();
+                // Keep the queue valid in case the engine is switched later.
+                self.events.mark_all();
+                if let Some(history) = self.history.as_mut() {
+                    history.touch_all();
+                }
+                changed
+            }
+            Engine::EventDriven => self.process_events(),
+        }
+    }
+
+    /// Update every wire, then every gate once.
+    /// Returns true if any gate output changed.
+    fn sweep(&mut self) -> bool {
+        for wire_id in 0..self.wires.len() {
+            let wire_state = self.get_wire_state(wire_id as u32);
+            let wire = &mut self.wires[wire_id];
+            wire.state = wire_state;
+        }
+
+        let mut changed = false;
+
+        for gate in self.gates.iter_mut() {
+            let wire_state = self.wires[gate.wire_in_id as usize].state;
+            changed |= gate.update_state(!wire_state, &self.timing, &mut self.rng);
+        }
+
+        changed
+    }
+
+    /// Recompute the wires whose drivers changed, then update the gates whose input
+    /// may have changed or which have not settled yet.
+    /// Returns true if any gate output changed.
+    fn process_events(&mut self) -> bool {
+        let wire_ids = self.events.take_wires();
+        for &wire_id in &wire_ids {
+            let wire_state = self.get_wire_state(wire_id);
+            let wire = &mut self.wires[wire_id as usize];
+            wire.state = wire_state;
+
+            for &gate_id in &wire.gate_out_ids {
+                self.events.activate_gate(gate_id);
+            }
+        }
+
+        let mut changed = false;
+
+        let gate_ids = self.events.take_gates();
+        for &gate_id in &gate_ids {
+            let gate = &mut self.gates[gate_id as usize];
+            let target_state = !self.wires[gate.wire_in_id as usize].state;
+
+            if gate.update_state(target_state, &self.timing, &mut self.rng) {
+                changed = true;
+                self.events.mark_wire(gate.wire_out_id as u32);
+            }
+
+            if !gate.is_settled(target_state) {
+                self.events.activate_gate(gate_id);
+            }
+        }
+
+        if let Some(history) = self.history.as_mut() {
+            history.touch(&wire_ids, &gate_ids);
+        }
+
+        changed
+    }
+
+    pub fn simulate(&mut self, steps: u32) {
+        for _ in 0..steps {
+            self.simulate_one_step();
+        }
+    }
+}
+
+/// What an empty pixel with wires on all four sides is.
+enum Neighbourhood {
+    Crossing,
+    Gate(Direction),
+    /// A single corner only joins two of the arms,
+    /// leaving something between a crossing and a gate.
+    Unrecognized,
+}
+
+/// Classify the pixel at (x, y), which must not be on the border of the image.
+/// Returns None unless it is empty with wires on all four sides, or if its corners
+/// already join every arm to a neighbour so it is just a hole between wires.
+fn neighbourhood(wire_map: &[Vec<i32>], x: usize, y: usize) -> Option<Neighbourhood> {
+    if wire_map[y - 1][x] == -1
+        || wire_map[y + 1][x] == -1
+        || wire_map[y][x - 1] == -1
+        || wire_map[y][x + 1] == -1
+        || wire_map[y][x] != -1
+    {
+        return None;
+    }
+
+    let bl = wire_map[y + 1][x - 1] != -1;
+    let br = wire_map[y + 1][x + 1] != -1;
+    let tl = wire_map[y - 1][x - 1] != -1;
+    let tr = wire_map[y - 1][x + 1] != -1;
+
+    // tl . tr
+    // .  .  .
+    // bl . br
+
+    match (bl, br, tl, tr) {
+        // crossing wire
+        // .#.
+        // #.#
+        // .#.
+        (false, false, false, false) => Some(Neighbourhood::Crossing),
+        // not gate up
+        // .#.
+        // #.#
+        // ###
+        (true, true, false, false) => Some(Neighbourhood::Gate(Direction::Up)),
+        // not gate down
+        // ###
+        // #.#
+        // .#.
+        (false, false, true, true) => Some(Neighbourhood::Gate(Direction::Down)),
+        // not gate left
+        // .##
+        // #.#
+        // .##
+        (false, true, false, true) => Some(Neighbourhood::Gate(Direction::Left)),
+        // not gate right
+        // ##.
+        // #.#
+        // ##.
+        (true, false, true, false) => Some(Neighbourhood::Gate(Direction::Right)),
+        // ##.
+        // #.#
+        // .#.
+        (true, false, false, false)
+        | (false, true, false, false)
+        | (false, false, true, false)
+        | (false, false, false, true) => Some(Neighbourhood::Unrecognized),
+        _ => None,
+    }
+}
+
+/// Pixels of the input and output wires of a gate at (x, y).
+fn gate_ends(x: usize, y: usize, direction: Direction) -> ((usize, usize), (usize, usize)) {
+    match direction {
+        Direction::Up => ((x, y + 1), (x, y - 1)),
+        Direction::Down => ((x, y - 1), (x, y + 1)),
+        Direction::Left => ((x + 1, y), (x - 1, y)),
+        Direction::Right => ((x - 1, y), (x + 1, y)),
+    }
+}
+
+/// One marker per wire and annotation class found on it.
+fn collect_markers(wire_map: &[Vec<i32>], class_map: &[Vec<PixelClass>]) -> Vec<Marker> {
+    let mut markers: Vec<Marker> = Vec::new();
+    let mut marked: HashSet<(u32, PixelClass)> = HashSet::new();
+
+    for (y, (row, classes)) in wire_map.iter().zip(class_map).enumerate() {
+        for (x, (&wire_id, &class)) in row.iter().zip(classes).enumerate() {
+            if wire_id == -1 || class == PixelClass::Wire {
+                continue;
+            }
+
+            let wire_id = wire_id as u32;
+            if !marked.insert((wire_id, class)) {
+                continue;
+            }
+
+            markers.push(Marker {
+                class,
+                wire_id,
+                x: x as u32,
+                y: y as u32,
+            });
+        }
+    }
+
+    markers
+}
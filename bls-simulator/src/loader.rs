@@ -0,0 +1,226 @@
+use std::{
+    fmt,
+    fs::File,
+    io,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+/// Decoded image ready to be handed to [`Simulator::from_pixels`](crate::Simulator::from_pixels).
+pub struct PixelBuffer {
+    pub width: u32,
+    pub height: u32,
+    /// Number of 8-bit channels per pixel, the last one being alpha.
+    pub components: u32,
+    pub data: Vec<u8>,
+}
+
+/// Channels of a pixel, in storage order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorLayout {
+    Gray,
+    GrayAlpha,
+    Rgb,
+    Rgba,
+}
+
+impl ColorLayout {
+    pub fn channels(self) -> usize {
+        match self {
+            Self::Gray => 1,
+            Self::GrayAlpha => 2,
+            Self::Rgb => 3,
+            Self::Rgba => 4,
+        }
+    }
+}
+
+/// Layout of a raw pixel buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PixelLayout {
+    pub color: ColorLayout,
+    /// Bits per channel, 8 or 16. 16-bit channels are in native byte order, like GPU textures.
+    pub bit_depth: u8,
+}
+
+impl PixelLayout {
+    pub fn rgba8() -> Self {
+        Self {
+            color: ColorLayout::Rgba,
+            bit_depth: 8,
+        }
+    }
+
+    pub fn bytes_per_pixel(self) -> usize {
+        self.color.channels() * self.bit_depth as usize / 8
+    }
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    Decode(png::DecodingError),
+    Unsupported(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read image: {}", err),
+            Self::Decode(err) => write!(f, "failed to decode png: {}", err),
+            Self::Unsupported(what) => write!(f, "unsupported image: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl PixelBuffer {
+    /// Normalize raw pixels of any supported layout to 8-bit RGBA.
+    ///
+    /// Gray is copied to every colour channel, a missing alpha channel is opaque,
+    /// and 16-bit channels keep their high byte.
+    pub fn from_raw(
+        width: u32,
+        height: u32,
+        layout: PixelLayout,
+        data: &[u8],
+    ) -> Result<Self, LoadError> {
+        if layout.bit_depth != 8 && layout.bit_depth != 16 {
+            return Err(LoadError::Unsupported(format!(
+                "bit depth {}",
+                layout.bit_depth
+            )));
+        }
+
+        let expected_len = width as usize * height as usize * layout.bytes_per_pixel();
+        if data.len() != expected_len {
+            return Err(LoadError::Unsupported(format!(
+                "{} bytes of pixels for a {}x{} image, expected {}",
+                data.len(),
+                width,
+                height,
+                expected_len
+            )));
+        }
+
+        let channels: Vec<u8> = if layout.bit_depth == 16 {
+            data.chunks_exact(2)
+                .map(|channel| (u16::from_ne_bytes([channel[0], channel[1]]) >> 8) as u8)
+                .collect()
+        } else {
+            data.to_vec()
+        };
+
+        let data = match layout.color {
+            ColorLayout::Rgba => channels,
+            ColorLayout::Rgb => channels
+                .chunks_exact(3)
+                .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 255])
+                .collect(),
+            ColorLayout::GrayAlpha => channels
+                .chunks_exact(2)
+                .flat_map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]])
+                .collect(),
+            ColorLayout::Gray => channels
+                .iter()
+                .flat_map(|&gray| [gray, gray, gray, 255])
+                .collect(),
+        };
+
+        Ok(Self {
+            width,
+            height,
+            components: 4,
+            data,
+        })
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<png::DecodingError> for LoadError {
+    fn from(err: png::DecodingError) -> Self {
+        Self::Decode(err)
+    }
+}
+
+/// Decode a PNG into an 8-bit RGBA pixel buffer.
+///
+/// Any color type and bit depth is accepted: palettes and low bit depths are
+/// expanded, transparency chunks become alpha and 16-bit channels are reduced to 8 bits.
+pub fn load_png<R: Read>(reader: R) -> Result<PixelBuffer, LoadError> {
+    let mut decoder = png::Decoder::new(reader);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+
+    let mut data = vec![0u8; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data)?;
+    data.truncate(info.buffer_size());
+
+    if info.bit_depth != png::BitDepth::Eight {
+        return Err(LoadError::Unsupported(format!(
+            "bit depth {:?}",
+            info.bit_depth
+        )));
+    }
+
+    let color = match info.color_type {
+        png::ColorType::Grayscale => ColorLayout::Gray,
+        png::ColorType::GrayscaleAlpha => ColorLayout::GrayAlpha,
+        png::ColorType::Rgb => ColorLayout::Rgb,
+        png::ColorType::Rgba => ColorLayout::Rgba,
+        color_type => {
+            return Err(LoadError::Unsupported(format!(
+                "color type {:?}",
+                color_type
+            )))
+        }
+    };
+
+    PixelBuffer::from_raw(
+        info.width,
+        info.height,
+        PixelLayout {
+            color,
+            bit_depth: 8,
+        },
+        &data,
+    )
+}
+
+/// Decode the PNG file at `path`, see [`load_png`].
+pub fn load_png_file<P: AsRef<Path>>(path: P) -> Result<PixelBuffer, LoadError> {
+    let file = File::open(path)?;
+    load_png(BufReader::new(file))
+}
+
+/// Encode an 8-bit RGBA pixel buffer as a PNG.
+pub fn save_png<W: Write>(writer: W, pixels: &PixelBuffer) -> io::Result<()> {
+    if pixels.components != 4 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} channels per pixel, expected 4", pixels.components),
+        ));
+    }
+
+    let mut encoder = png::Encoder::new(writer, pixels.width, pixels.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&pixels.data)?;
+    writer.finish()?;
+
+    Ok(())
+}
+
+/// Write the pixel buffer to a PNG file at `path`, see [`save_png`].
+pub fn save_png_file<P: AsRef<Path>>(path: P, pixels: &PixelBuffer) -> io::Result<()> {
+    let file = File::create(path)?;
+    save_png(BufWriter::new(file), pixels)
+}
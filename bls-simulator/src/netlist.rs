@@ -0,0 +1,176 @@
+//! Extracted netlist of a circuit, exportable as JSON or Graphviz DOT.
+//!
+//! The JSON document has the following shape. Coordinates are pixels with the
+//! origin at the top left corner of the image, bounds are inclusive.
+//!
+//! ```json
+//! {
+//!   "width": 64,
+//!   "height": 32,
+//!   "wires": [
+//!     {
+//!       "id": 0,
+//!       "bounds": { "min_x": 1, "min_y": 4, "max_x": 20, "max_y": 4 },
+//!       "pixel_count": 20,
+//!       "drivers": [0],
+//!       "loads": [1, 2]
+//!     }
+//!   ],
+//!   "gates": [
+//!     { "id": 0, "x": 21, "y": 4, "direction": "right", "input": 3, "output": 0 }
+//!   ]
+//! }
+//! ```
+//!
+//! - `wires[].bounds`: `null` if the wire has no pixels.
+//! - `wires[].drivers`: ids of the gates driving the wire. A wire with several
+//!   drivers is ON if any of them is ON.
+//! - `wires[].loads`: ids of the gates whose input is the wire.
+//! - `gates[].direction`: where the output points, one of `up`, `down`, `left`, `right`.
+//! - `gates[].input` / `gates[].output`: wire ids.
+
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::{Direction, Simulator};
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Netlist {
+    pub width: u32,
+    pub height: u32,
+    pub wires: Vec<NetlistWire>,
+    pub gates: Vec<NetlistGate>,
+}
+
+/// Inclusive pixel bounding box.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct PixelBounds {
+    pub min_x: u32,
+    pub min_y: u32,
+    pub max_x: u32,
+    pub max_y: u32,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct NetlistWire {
+    pub id: u32,
+    pub bounds: Option<PixelBounds>,
+    pub pixel_count: u32,
+    pub drivers: Vec<u32>,
+    pub loads: Vec<u32>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct NetlistGate {
+    pub id: u32,
+    pub x: u32,
+    pub y: u32,
+    pub direction: Direction,
+    pub input: u32,
+    pub output: u32,
+}
+
+impl Netlist {
+    pub fn from_simulator(simulator: &Simulator) -> Self {
+        let mut bounds: Vec<Option<PixelBounds>> = vec![None; simulator.wires.len()];
+        let mut pixel_counts = vec![0u32; simulator.wires.len()];
+
+        for (y, row) in simulator.wire_map.iter().enumerate() {
+            for (x, &wire_id) in row.iter().enumerate() {
+                if wire_id == -1 {
+                    continue;
+                }
+
+                let (x, y) = (x as u32, y as u32);
+                pixel_counts[wire_id as usize] += 1;
+                bounds[wire_id as usize] = Some(match bounds[wire_id as usize] {
+                    Some(b) => PixelBounds {
+                        min_x: b.min_x.min(x),
+                        min_y: b.min_y.min(y),
+                        max_x: b.max_x.max(x),
+                        max_y: b.max_y.max(y),
+                    },
+                    None => PixelBounds {
+                        min_x: x,
+                        min_y: y,
+                        max_x: x,
+                        max_y: y,
+                    },
+                });
+            }
+        }
+
+        let wires = simulator
+            .wires
+            .iter()
+            .enumerate()
+            .map(|(wire_id, wire)| NetlistWire {
+                id: wire_id as u32,
+                bounds: bounds[wire_id],
+                pixel_count: pixel_counts[wire_id],
+                drivers: wire.gate_in_ids.clone(),
+                loads: wire.gate_out_ids.clone(),
+            })
+            .collect();
+
+        let gates = simulator
+            .gates
+            .iter()
+            .enumerate()
+            .map(|(gate_id, gate)| NetlistGate {
+                id: gate_id as u32,
+                x: gate.x,
+                y: gate.y,
+                direction: gate.direction,
+                input: gate.wire_in_id as u32,
+                output: gate.wire_out_id as u32,
+            })
+            .collect();
+
+        Self {
+            width: simulator.width,
+            height: simulator.height,
+            wires,
+            gates,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        // Serializing plain structs can't fail.
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Write the netlist as a Graphviz digraph, with wires as ellipses and gates as triangles.
+    pub fn write_dot<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "digraph circuit {{")?;
+        writeln!(out, "    rankdir=LR;")?;
+
+        for wire in &self.wires {
+            match &wire.bounds {
+                Some(b) => writeln!(
+                    out,
+                    "    w{} [shape=ellipse, label=\"w{}\\n({}, {})-({}, {})\"];",
+                    wire.id, wire.id, b.min_x, b.min_y, b.max_x, b.max_y
+                )?,
+                None => writeln!(out, "    w{} [shape=ellipse];", wire.id)?,
+            }
+        }
+
+        for gate in &self.gates {
+            writeln!(
+                out,
+                "    g{} [shape=triangle, orientation=-90, label=\"g{} {}\\n({}, {})\"];",
+                gate.id,
+                gate.id,
+                gate.direction.arrow(),
+                gate.x,
+                gate.y
+            )?;
+            writeln!(out, "    w{} -> g{};", gate.input, gate.id)?;
+            writeln!(out, "    g{} -> w{};", gate.id, gate.output)?;
+        }
+
+        writeln!(out, "}}")
+    }
+}
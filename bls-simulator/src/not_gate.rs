@@ -0,0 +1,109 @@
+use std::fmt;
+
+use rand::Rng;
+use serde::Serialize;
+
+use crate::TimingModel;
+
+/// Direction the output of a gate points to, seen from its input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::Down => "down",
+            Self::Left => "left",
+            Self::Right => "right",
+        }
+    }
+
+    pub fn arrow(self) -> char {
+        match self {
+            Self::Up => '↑',
+            Self::Down => '↓',
+            Self::Left => '←',
+            Self::Right => '→',
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone)]
+pub struct NotGate {
+    pub wire_in_id: i32,
+    pub wire_out_id: i32,
+
+    /// Real state of the output of the gate.
+    pub state: bool,
+
+    pub x: u32,
+    pub y: u32,
+    pub direction: Direction,
+
+    /// This value is used to delay changes of the state, mimic the real world thingy.
+    pub slow_state: f32,
+}
+
+impl NotGate {
+    pub fn new(wire_in_id: i32, wire_out_id: i32, x: u32, y: u32, direction: Direction) -> Self {
+        Self {
+            wire_in_id,
+            wire_out_id,
+            state: false,
+            slow_state: 0.0,
+            x,
+            y,
+            direction,
+        }
+    }
+
+    /// Whether the output has fully settled at `state`, so further updates towards it are no-ops.
+    pub fn is_settled(&self, state: bool) -> bool {
+        if state {
+            self.state && self.slow_state >= 1.0
+        } else {
+            !self.state && self.slow_state <= 0.0
+        }
+    }
+
+    /// Move the gate output towards `state` following the timing model.
+    /// Returns true if the output state changed.
+    pub fn update_state<R: Rng>(&mut self, state: bool, timing: &TimingModel, rng: &mut R) -> bool {
+        if self.is_settled(state) {
+            return false;
+        }
+
+        let prev_state = self.state;
+
+        if state {
+            self.slow_state += timing.delta(true, rng);
+
+            if self.slow_state >= 1.0 {
+                self.slow_state = 1.0;
+                self.state = true;
+            }
+        } else {
+            self.slow_state -= timing.delta(false, rng);
+
+            if self.slow_state <= 0.0 {
+                self.slow_state = 0.0;
+                self.state = false;
+            }
+        }
+
+        self.state != prev_state
+    }
+}
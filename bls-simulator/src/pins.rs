@@ -0,0 +1,308 @@
+//! Named pins and buses of a circuit, read from a TOML file next to its image.
+//!
+//! `circuit.png` is described by `circuit.png.toml`. Every pin is given by the
+//! coordinates of any pixel of its wire.
+//!
+//! ```toml
+//! [inputs]
+//! a = [2, 1]
+//! b = [6, 1]
+//!
+//! [outputs]
+//! sum = [34, 39]
+//!
+//! [buses.data]
+//! direction = "input"
+//! bit_order = "msb_first"
+//! pins = [[10, 1], [14, 1], [18, 1]]
+//! ```
+//!
+//! - `buses.*.direction`: `input` or `output`.
+//! - `buses.*.bit_order`: order of `pins`, `lsb_first` (the default) or `msb_first`.
+//! - `buses.*.pins`: at most 64 pins.
+//!
+//! Names are unique across pins and buses.
+//!
+//! Wires drawn with input or output pin colours, see [`Classifier`](crate::Classifier),
+//! are pins too, named `wN` after their wire unless a pin of the file already names them.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{Marker, PixelClass, PortDirection, Simulator, WireBus};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BitOrder {
+    #[default]
+    LsbFirst,
+    MsbFirst,
+}
+
+/// Content of a pin file, before its coordinates are resolved to wires.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PinFile {
+    #[serde(default)]
+    pub inputs: BTreeMap<String, [u32; 2]>,
+    #[serde(default)]
+    pub outputs: BTreeMap<String, [u32; 2]>,
+    #[serde(default)]
+    pub buses: BTreeMap<String, BusSpec>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BusSpec {
+    pub direction: PortDirection,
+    #[serde(default)]
+    pub bit_order: BitOrder,
+    pub pins: Vec<[u32; 2]>,
+}
+
+#[derive(Debug)]
+pub enum PinError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    DuplicateName(String),
+    EmptyBus(String),
+    BusTooWide(String),
+    NotOnWire { name: String, x: u32, y: u32 },
+}
+
+impl fmt::Display for PinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read pin file: {}", err),
+            Self::Parse(err) => write!(f, "invalid pin file: {}", err),
+            Self::DuplicateName(name) => write!(f, "pin {} is defined more than once", name),
+            Self::EmptyBus(name) => write!(f, "bus {} has no pins", name),
+            Self::BusTooWide(name) => write!(f, "bus {} has more than 64 pins", name),
+            Self::NotOnWire { name, x, y } => {
+                write!(f, "pin {} at ({}, {}) is not on a wire", name, x, y)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PinError {}
+
+impl From<io::Error> for PinError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<toml::de::Error> for PinError {
+    fn from(err: toml::de::Error) -> Self {
+        Self::Parse(err)
+    }
+}
+
+impl PinFile {
+    pub fn parse(text: &str) -> Result<Self, PinError> {
+        Ok(toml::from_str(text)?)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, PinError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+}
+
+/// Path of the pin file describing the image at `image_path`.
+pub fn pin_file_path<P: AsRef<Path>>(image_path: P) -> PathBuf {
+    let mut path = image_path.as_ref().as_os_str().to_owned();
+    path.push(".toml");
+    PathBuf::from(path)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pin {
+    pub name: String,
+    pub direction: PortDirection,
+    pub x: u32,
+    pub y: u32,
+    pub wire_id: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bus {
+    pub name: String,
+    pub direction: PortDirection,
+    /// Pins of every bit, least significant first.
+    pub bits: Vec<Pin>,
+}
+
+impl Bus {
+    pub fn wire_bus(&self) -> WireBus {
+        WireBus::new(
+            self.bits.iter().map(|bit| bit.wire_id).collect(),
+            BitOrder::LsbFirst,
+        )
+    }
+}
+
+/// Pins and buses resolved to the wires of a circuit.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Pins {
+    pub pins: Vec<Pin>,
+    pub buses: Vec<Bus>,
+}
+
+impl Pins {
+    /// Find the wire of every pin of `file` in the circuit of `simulator`.
+    pub fn resolve(file: &PinFile, simulator: &Simulator) -> Result<Self, PinError> {
+        let mut names = HashSet::new();
+        let mut check_name = |name: &str| {
+            if names.insert(name.to_string()) {
+                Ok(())
+            } else {
+                Err(PinError::DuplicateName(name.to_string()))
+            }
+        };
+
+        let resolve_pin = |name: String, direction, [x, y]: [u32; 2]| -> Result<Pin, PinError> {
+            let wire_id = simulator
+                .wire_id_at(x, y)
+                .ok_or_else(|| PinError::NotOnWire {
+                    name: name.clone(),
+                    x,
+                    y,
+                })?;
+
+            Ok(Pin {
+                name,
+                direction,
+                x,
+                y,
+                wire_id,
+            })
+        };
+
+        let mut pins = Vec::new();
+        let named_pins = file
+            .inputs
+            .iter()
+            .map(|pin| (pin, PortDirection::Input))
+            .chain(file.outputs.iter().map(|pin| (pin, PortDirection::Output)));
+
+        for ((name, &position), direction) in named_pins {
+            check_name(name)?;
+            pins.push(resolve_pin(name.clone(), direction, position)?);
+        }
+
+        let mut buses = Vec::new();
+
+        for (name, spec) in &file.buses {
+            check_name(name)?;
+            if spec.pins.is_empty() {
+                return Err(PinError::EmptyBus(name.clone()));
+            }
+            if spec.pins.len() > 64 {
+                return Err(PinError::BusTooWide(name.clone()));
+            }
+
+            let width = spec.pins.len();
+            let mut bits = Vec::with_capacity(width);
+
+            for bit in 0..width {
+                let position = match spec.bit_order {
+                    BitOrder::LsbFirst => spec.pins[bit],
+                    BitOrder::MsbFirst => spec.pins[width - 1 - bit],
+                };
+                bits.push(resolve_pin(
+                    format!("{}[{}]", name, bit),
+                    spec.direction,
+                    position,
+                )?);
+            }
+
+            buses.push(Bus {
+                name: name.clone(),
+                direction: spec.direction,
+                bits,
+            });
+        }
+
+        Ok(Self { pins, buses })
+    }
+
+    /// Find the wire of every pin again after the circuit was extracted again.
+    /// Pins, and buses with a bit, no longer on a wire are dropped.
+    pub(crate) fn relocate(&mut self, simulator: &Simulator) {
+        let relocate = |pin: &mut Pin| match simulator.wire_id_at(pin.x, pin.y) {
+            Some(wire_id) => {
+                pin.wire_id = wire_id;
+                true
+            }
+            None => false,
+        };
+
+        self.pins.retain_mut(relocate);
+        self.buses
+            .retain_mut(|bus| bus.bits.iter_mut().all(relocate));
+    }
+
+    /// Add a pin for every input or output pin marker whose wire has no pin going the
+    /// same way yet, named after the wire.
+    pub(crate) fn add_markers(&mut self, markers: &[Marker]) {
+        for marker in markers {
+            let direction = match marker_direction(marker) {
+                Some(direction) => direction,
+                None => continue,
+            };
+
+            let name = format!("w{}", marker.wire_id);
+            let named = self.pins.iter().any(|pin| {
+                pin.name == name || (pin.wire_id == marker.wire_id && pin.direction == direction)
+            });
+            if named || self.bus(&name).is_some() {
+                continue;
+            }
+
+            self.pins.push(Pin {
+                name,
+                direction,
+                x: marker.x,
+                y: marker.y,
+                wire_id: marker.wire_id,
+            });
+        }
+    }
+
+    /// Remove the pins [`Pins::add_markers`] added for `markers`.
+    pub(crate) fn remove_markers(&mut self, markers: &[Marker]) {
+        self.pins.retain(|pin| {
+            !markers.iter().any(|marker| {
+                marker_direction(marker) == Some(pin.direction)
+                    && (marker.x, marker.y) == (pin.x, pin.y)
+                    && pin.name == format!("w{}", marker.wire_id)
+            })
+        });
+    }
+
+    pub fn pin(&self, name: &str) -> Option<&Pin> {
+        self.pins.iter().find(|pin| pin.name == name)
+    }
+
+    pub fn bus(&self, name: &str) -> Option<&Bus> {
+        self.buses.iter().find(|bus| bus.name == name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pins.is_empty() && self.buses.is_empty()
+    }
+}
+
+fn marker_direction(marker: &Marker) -> Option<PortDirection> {
+    match marker.class {
+        PixelClass::InputPin => Some(PortDirection::Input),
+        PixelClass::OutputPin => Some(PortDirection::Output),
+        _ => None,
+    }
+}
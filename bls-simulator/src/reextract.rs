@@ -0,0 +1,289 @@
+use std::collections::HashSet;
+
+use crate::{
+    collect_diagnostics, gate_ends, image_hash, neighbourhood, CarryOver, DiagnosticKind,
+    EventQueue, Marker, Neighbourhood, NotGate, PixelClass, PixelRect, Simulator, UnionFind, Wire,
+};
+
+impl Simulator {
+    /// Extract the circuit again after the pixels inside `rect` changed, only looking at
+    /// the wires around them.
+    ///
+    /// `data` holds the whole edited image, of the same size as before, see
+    /// [`Simulator::from_pixels`]. The circuit is the one a full extraction would give, up
+    /// to the order of wires. Wires not touching the edit keep their state, and so do wires
+    /// made of exactly the same pixels as before and gates outside the edit. Pins and probes
+    /// follow their wires or are dropped with them, and the history starts over.
+    pub fn reextract(&mut self, data: &[u8], components: u32, rect: PixelRect) -> CarryOver {
+        let (width, height) = (self.width, self.height);
+        let components = components as usize;
+        assert_eq!(
+            data.len(),
+            width as usize * height as usize * components,
+            "pixel buffer does not match the image size"
+        );
+
+        let rect = rect.clip(width, height);
+        // A changed pixel changes the neighbourhoods of the pixels next to it,
+        // which can join or split the wires of their arms one pixel further.
+        let around = rect.expand(1, width, height);
+        let reach = rect.expand(2, width, height);
+
+        let previous_count = self.wires.len();
+        let mut affected = vec![false; previous_count];
+        for y in reach.y..reach.y + reach.height {
+            for x in reach.x..reach.x + reach.width {
+                if let Some(wire_id) = self.wire_id_at(x, y) {
+                    affected[wire_id as usize] = true;
+                }
+            }
+        }
+
+        // Label the pixels of affected wires and the wires drawn in `rect` with runs,
+        // numbered after the previous wires so both can share the wire map for now.
+        let base = previous_count as i32;
+        let mut runs: Vec<i32> = Vec::new();
+        let mut previous_sizes = vec![0usize; previous_count];
+        // Position, previous wire and class of every relabeled pixel, in reading order.
+        let mut relabeled: Vec<(usize, usize, i32, PixelClass)> = Vec::new();
+
+        for y in 0..height as usize {
+            let mut prev_is_relabeled = false;
+
+            for x in 0..width as usize {
+                let previous_id = self.wire_map[y][x];
+                if previous_id != -1 {
+                    previous_sizes[previous_id as usize] += 1;
+                }
+
+                let is_relabeled = rect.contains(x as u32, y as u32)
+                    || (previous_id != -1 && affected[previous_id as usize]);
+                if !is_relabeled {
+                    prev_is_relabeled = false;
+                    continue;
+                }
+
+                let start = (y * width as usize + x) * components;
+                let class = self.classifier.classify(&data[start..start + components]);
+                if !class.is_conductive() {
+                    self.wire_map[y][x] = -1;
+                    prev_is_relabeled = false;
+                    continue;
+                }
+
+                if !prev_is_relabeled {
+                    runs.push(-1);
+                }
+                let run = base + runs.len() as i32 - 1;
+                self.wire_map[y][x] = run;
+
+                if y > 0 && self.wire_map[y - 1][x] >= base {
+                    runs.merge(self.wire_map[y - 1][x] - base, run - base);
+                }
+
+                relabeled.push((x, y, previous_id, class));
+                prev_is_relabeled = true;
+            }
+        }
+
+        // Crossings next to relabeled pixels join their arms again.
+        for &(x, y, _, _) in &relabeled {
+            let centers = [
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+            ];
+
+            for (x, y) in centers {
+                if !(1..(width as usize).saturating_sub(1)).contains(&x)
+                    || !(1..(height as usize).saturating_sub(1)).contains(&y)
+                {
+                    continue;
+                }
+                if !matches!(
+                    neighbourhood(&self.wire_map, x, y),
+                    Some(Neighbourhood::Crossing)
+                ) {
+                    continue;
+                }
+
+                let map = &self.wire_map;
+                for (a, b) in [
+                    (map[y][x - 1], map[y][x + 1]),
+                    (map[y - 1][x], map[y + 1][x]),
+                ] {
+                    if a >= base && b >= base {
+                        runs.merge(a - base, b - base);
+                    }
+                }
+            }
+        }
+
+        // Gates and unrecognized patterns are only found again around the edit.
+        let mut unrecognized: Vec<(u32, u32)> = self
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| {
+                diagnostic.kind == DiagnosticKind::UnrecognizedPattern
+                    && !around.contains(diagnostic.x, diagnostic.y)
+            })
+            .map(|diagnostic| (diagnostic.x, diagnostic.y))
+            .collect();
+        let mut gates: Vec<NotGate> = std::mem::take(&mut self.gates)
+            .into_iter()
+            .filter(|gate| !around.contains(gate.x, gate.y))
+            .collect();
+        let kept_gates = gates.len();
+
+        for y in around.y.max(1)..(around.y + around.height).min(height.saturating_sub(1)) {
+            for x in around.x.max(1)..(around.x + around.width).min(width.saturating_sub(1)) {
+                match neighbourhood(&self.wire_map, x as usize, y as usize) {
+                    Some(Neighbourhood::Gate(direction)) => {
+                        gates.push(NotGate::new(-1, -1, x, y, direction))
+                    }
+                    Some(Neighbourhood::Unrecognized) => unrecognized.push((x, y)),
+                    _ => {}
+                }
+            }
+        }
+
+        // Keep the reading order a full extraction gives.
+        gates.sort_by_key(|gate| (gate.y, gate.x));
+        unrecognized.sort_by_key(|&(x, y)| (y, x));
+
+        // compress wire id, unaffected wires first
+        let previous_states: Vec<bool> = self.wires.iter().map(|wire| wire.state).collect();
+        let mut wire_remap = vec![-1i32; previous_count];
+        let mut wires: Vec<Wire> = Vec::new();
+
+        for (wire_id, &state) in previous_states.iter().enumerate() {
+            if affected[wire_id] {
+                continue;
+            }
+
+            wire_remap[wire_id] = wires.len() as i32;
+            wires.push(Wire {
+                state,
+                ..Wire::new()
+            });
+        }
+
+        let kept_wires = wires.len();
+        let mut run_remap = vec![-1i32; runs.len()];
+
+        for run in 0..runs.len() as i32 {
+            if runs.is_root(run) {
+                run_remap[run as usize] = wires.len() as i32;
+                wires.push(Wire::new());
+            }
+        }
+
+        for row in self.wire_map.iter_mut() {
+            for cell in row.iter_mut() {
+                if *cell >= base {
+                    *cell = run_remap[runs.find(*cell - base) as usize];
+                } else if *cell != -1 {
+                    *cell = wire_remap[*cell as usize];
+                }
+            }
+        }
+
+        // New wires made of every pixel of a previous wire and nothing else keep its state.
+        let new_count = wires.len() - kept_wires;
+        let mut sizes = vec![0usize; new_count];
+        let mut candidates: Vec<Option<i32>> = vec![None; new_count];
+        let mut changed = vec![false; new_count];
+        for &(x, y, previous_id, _) in &relabeled {
+            let wire_id = self.wire_map[y][x];
+            let index = wire_id as usize - kept_wires;
+            sizes[index] += 1;
+
+            match (previous_id, candidates[index]) {
+                (-1, _) => changed[index] = true,
+                (previous_id, None) => candidates[index] = Some(previous_id),
+                (previous_id, Some(candidate)) => changed[index] |= previous_id != candidate,
+            }
+        }
+
+        let mut carried_wires = kept_wires;
+        for (index, wire) in wires[kept_wires..].iter_mut().enumerate() {
+            if let Some(previous_id) = candidates[index] {
+                if !changed[index] && previous_sizes[previous_id as usize] == sizes[index] {
+                    wire.state = previous_states[previous_id as usize];
+                    carried_wires += 1;
+                }
+            }
+        }
+
+        // connect gates
+        for (gate_id, gate) in gates.iter_mut().enumerate() {
+            let ((in_x, in_y), (out_x, out_y)) =
+                gate_ends(gate.x as usize, gate.y as usize, gate.direction);
+            gate.wire_in_id = self.wire_map[in_y][in_x];
+            gate.wire_out_id = self.wire_map[out_y][out_x];
+
+            wires[gate.wire_out_id as usize]
+                .gate_in_ids
+                .push(gate_id as u32);
+            wires[gate.wire_in_id as usize]
+                .gate_out_ids
+                .push(gate_id as u32);
+        }
+
+        let mut markers: Vec<Marker> = self
+            .markers
+            .iter()
+            .filter(|marker| !affected[marker.wire_id as usize])
+            .map(|marker| Marker {
+                wire_id: wire_remap[marker.wire_id as usize] as u32,
+                ..*marker
+            })
+            .collect();
+        let mut marked: HashSet<(u32, PixelClass)> = markers
+            .iter()
+            .map(|marker| (marker.wire_id, marker.class))
+            .collect();
+
+        for &(x, y, _, class) in &relabeled {
+            let wire_id = self.wire_map[y][x] as u32;
+            if class == PixelClass::Wire || !marked.insert((wire_id, class)) {
+                continue;
+            }
+
+            markers.push(Marker {
+                class,
+                wire_id,
+                x: x as u32,
+                y: y as u32,
+            });
+        }
+        markers.sort_by_key(|marker| (marker.y, marker.x));
+
+        // Pins named after the wires of their markers are named again below.
+        let mut pins = std::mem::take(&mut self.pins);
+        pins.remove_markers(&self.markers);
+
+        self.diagnostics = collect_diagnostics(&self.wire_map, &wires, &gates, &unrecognized);
+        self.markers = markers;
+        self.image_hash = image_hash(width, height, components as u32, data);
+        self.events = EventQueue::new(wires.len(), gates.len());
+        self.wires = wires;
+        self.gates = gates;
+
+        pins.relocate(self);
+        pins.add_markers(&self.markers);
+        self.pins = pins;
+        let mut recorder = std::mem::take(&mut self.recorder);
+        recorder.relocate(self);
+        self.recorder = recorder;
+
+        self.invalidate();
+        self.reset_history();
+
+        CarryOver {
+            wires: carried_wires,
+            gates: kept_gates,
+        }
+    }
+}
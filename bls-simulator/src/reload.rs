@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use crate::Simulator;
+
+/// How much state [`Simulator::carry_over`] or [`Simulator::reextract`] kept.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CarryOver {
+    pub wires: usize,
+    pub gates: usize,
+}
+
+impl Simulator {
+    /// Continue from `previous`, the circuit of an earlier version of the same image.
+    ///
+    /// Wires made of exactly the same pixels keep their state, and so do gates at the same
+    /// pixel and in the same direction whose input and output wires both did. Everything
+    /// else starts low. The step counter, gate delay RNG, timing model and engine are taken
+    /// from `previous`, and the history starts over.
+    pub fn carry_over(&mut self, previous: &Simulator) -> CarryOver {
+        let wire_ids = self.unchanged_wires(previous);
+        let mut carry_over = CarryOver::default();
+
+        for (wire, previous_id) in self.wires.iter_mut().zip(&wire_ids) {
+            if let Some(previous_id) = previous_id {
+                wire.state = previous.wires[*previous_id as usize].state;
+                carry_over.wires += 1;
+            }
+        }
+
+        let previous_gates: HashMap<_, _> = previous
+            .gates
+            .iter()
+            .map(|gate| ((gate.x, gate.y, gate.direction), gate))
+            .collect();
+
+        for gate in &mut self.gates {
+            let previous_gate = match previous_gates.get(&(gate.x, gate.y, gate.direction)) {
+                Some(previous_gate) => previous_gate,
+                None => continue,
+            };
+
+            if wire_ids[gate.wire_in_id as usize] != Some(previous_gate.wire_in_id as u32)
+                || wire_ids[gate.wire_out_id as usize] != Some(previous_gate.wire_out_id as u32)
+            {
+                continue;
+            }
+
+            gate.state = previous_gate.state;
+            gate.slow_state = previous_gate.slow_state;
+            carry_over.gates += 1;
+        }
+
+        self.step_count = previous.step_count;
+        self.seed = previous.seed;
+        self.rng = previous.rng.clone();
+        self.timing = previous.timing;
+        self.engine = previous.engine;
+        self.invalidate();
+        self.reset_history();
+
+        carry_over
+    }
+
+    /// Wire of `previous` made of the same pixels as each wire, if any.
+    fn unchanged_wires(&self, previous: &Simulator) -> Vec<Option<u32>> {
+        let mut previous_sizes = vec![0usize; previous.wires.len()];
+        for &wire_id in previous.wire_map.iter().flatten() {
+            if wire_id != -1 {
+                previous_sizes[wire_id as usize] += 1;
+            }
+        }
+
+        let mut sizes = vec![0usize; self.wires.len()];
+        let mut candidates: Vec<Option<u32>> = vec![None; self.wires.len()];
+        let mut changed = vec![false; self.wires.len()];
+
+        for (y, row) in self.wire_map.iter().enumerate() {
+            for (x, &wire_id) in row.iter().enumerate() {
+                if wire_id == -1 {
+                    continue;
+                }
+
+                let wire_id = wire_id as usize;
+                sizes[wire_id] += 1;
+
+                match (previous.wire_id_at(x as u32, y as u32), candidates[wire_id]) {
+                    (None, _) => changed[wire_id] = true,
+                    (Some(previous_id), None) => candidates[wire_id] = Some(previous_id),
+                    (Some(previous_id), Some(candidate)) => {
+                        changed[wire_id] |= previous_id != candidate
+                    }
+                }
+            }
+        }
+
+        // Every pixel of the wire belongs to the same previous wire, which has no other pixels.
+        candidates
+            .iter()
+            .zip(&sizes)
+            .zip(&changed)
+            .map(|((&candidate, &size), &changed)| {
+                candidate
+                    .filter(|&previous_id| !changed && previous_sizes[previous_id as usize] == size)
+            })
+            .collect()
+    }
+}
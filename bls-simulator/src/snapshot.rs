@@ -0,0 +1,224 @@
+//! Snapshots of the state of a running circuit, saved as JSON.
+//!
+//! A snapshot holds what changes while simulating: the state of every wire and gate,
+//! the gate delay RNG, the step counter and the work the event driven engine has left
+//! for the next step. It can only be restored onto a simulator extracted from the same
+//! image, which is checked with a hash of its pixels.
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
+
+use crate::{NotGate, Simulator};
+
+/// FNV-1a hash of an image, stable across platforms and Rust versions.
+pub fn image_hash(width: u32, height: u32, components: u32, data: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let header = [width, height, components]
+        .into_iter()
+        .flat_map(u32::to_le_bytes);
+
+    header
+        .chain(data.iter().copied())
+        .fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(PRIME)
+        })
+}
+
+/// Path of the snapshot file of the image at `image_path`.
+pub fn snapshot_file_path<P: AsRef<Path>>(image_path: P) -> PathBuf {
+    let mut path = image_path.as_ref().as_os_str().to_owned();
+    path.push(".snapshot.json");
+    PathBuf::from(path)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GateSnapshot {
+    pub state: bool,
+    pub slow_state: f32,
+}
+
+impl GateSnapshot {
+    pub fn of(gate: &NotGate) -> Self {
+        Self {
+            state: gate.state,
+            slow_state: gate.slow_state,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// [`image_hash`] of the image the circuit was extracted from.
+    pub image_hash: u64,
+    pub step_count: u64,
+    pub seed: u64,
+    pub rng: Pcg32,
+    /// State of every wire, one `0` or `1` per wire.
+    pub wires: String,
+    pub gates: Vec<GateSnapshot>,
+    /// Wires and gates the next step looks at first, see [`EventQueue`](crate::EventQueue).
+    pub pending_wires: Vec<u32>,
+    pub active_gates: Vec<u32>,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// The snapshot was taken on another image.
+    ImageMismatch {
+        expected: u64,
+        found: u64,
+    },
+    /// The snapshot does not match the wires and gates of the circuit.
+    Invalid(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to access snapshot: {}", err),
+            Self::Json(err) => write!(f, "invalid snapshot: {}", err),
+            Self::ImageMismatch { expected, found } => write!(
+                f,
+                "snapshot was taken on image {:016x}, not {:016x}",
+                found, expected
+            ),
+            Self::Invalid(message) => write!(f, "invalid snapshot: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<io::Error> for SnapshotError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SnapshotError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+impl Snapshot {
+    pub fn parse(text: &str) -> Result<Self, SnapshotError> {
+        Ok(serde_json::from_str(text)?)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SnapshotError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SnapshotError> {
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+impl Simulator {
+    /// Capture the state of the running circuit.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            image_hash: self.image_hash(),
+            step_count: self.step_count,
+            seed: self.seed,
+            rng: self.rng.clone(),
+            wires: self
+                .wires
+                .iter()
+                .map(|wire| if wire.state { '1' } else { '0' })
+                .collect(),
+            gates: self.gates.iter().map(GateSnapshot::of).collect(),
+            pending_wires: self.events.pending_wires().to_vec(),
+            active_gates: self.events.active_gates().to_vec(),
+        }
+    }
+
+    /// Continue from `snapshot`, as if it had been taken on this simulator.
+    ///
+    /// Timing model, engine, pins and the waveform recorder are left as they are,
+    /// the history starts over.
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<(), SnapshotError> {
+        if snapshot.image_hash != self.image_hash() {
+            return Err(SnapshotError::ImageMismatch {
+                expected: self.image_hash(),
+                found: snapshot.image_hash,
+            });
+        }
+        if snapshot.wires.len() != self.wires.len() {
+            return Err(SnapshotError::Invalid(format!(
+                "{} wires, expected {}",
+                snapshot.wires.len(),
+                self.wires.len()
+            )));
+        }
+        if snapshot.gates.len() != self.gates.len() {
+            return Err(SnapshotError::Invalid(format!(
+                "{} gates, expected {}",
+                snapshot.gates.len(),
+                self.gates.len()
+            )));
+        }
+
+        let wire_states = snapshot
+            .wires
+            .chars()
+            .map(|state| match state {
+                '0' => Ok(false),
+                '1' => Ok(true),
+                _ => Err(SnapshotError::Invalid(format!(
+                    "wire state {:?}, expected 0 or 1",
+                    state
+                ))),
+            })
+            .collect::<Result<Vec<bool>, _>>()?;
+
+        if let Some(wire_id) = snapshot
+            .pending_wires
+            .iter()
+            .find(|&&wire_id| wire_id as usize >= self.wires.len())
+        {
+            return Err(SnapshotError::Invalid(format!("no wire {}", wire_id)));
+        }
+        if let Some(gate_id) = snapshot
+            .active_gates
+            .iter()
+            .find(|&&gate_id| gate_id as usize >= self.gates.len())
+        {
+            return Err(SnapshotError::Invalid(format!("no gate {}", gate_id)));
+        }
+
+        for (wire, state) in self.wires.iter_mut().zip(wire_states) {
+            wire.state = state;
+        }
+        for (gate, snapshot) in self.gates.iter_mut().zip(&snapshot.gates) {
+            gate.state = snapshot.state;
+            gate.slow_state = snapshot.slow_state;
+        }
+
+        self.seed = snapshot.seed;
+        self.rng = snapshot.rng.clone();
+        self.step_count = snapshot.step_count;
+
+        self.events.clear();
+        for &wire_id in &snapshot.pending_wires {
+            self.events.mark_wire(wire_id);
+        }
+        for &gate_id in &snapshot.active_gates {
+            self.events.activate_gate(gate_id);
+        }
+        self.reset_history();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+use crate::Simulator;
+
+/// Outcome of [`Simulator::simulate_until_stable`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Stability {
+    /// Nothing changes anymore since `steps` steps.
+    Stable { steps: u32 },
+    /// The circuit repeats the same `period` states since `steps` steps.
+    /// `wire_ids` are the wires changing within a period.
+    Oscillating {
+        steps: u32,
+        period: u32,
+        wire_ids: Vec<u32>,
+    },
+    /// Neither a fixed point nor a cycle was found within `steps` steps.
+    Unsettled { steps: u32 },
+}
+
+impl Stability {
+    pub fn is_stable(&self) -> bool {
+        matches!(self, Self::Stable { .. })
+    }
+
+    /// Number of steps it took to settle or start oscillating,
+    /// or that were simulated without doing either.
+    pub fn steps(&self) -> u32 {
+        match *self {
+            Self::Stable { steps }
+            | Self::Oscillating { steps, .. }
+            | Self::Unsettled { steps } => steps,
+        }
+    }
+}
+
+impl Simulator {
+    /// Simulate until the circuit reaches a fixed point or starts repeating itself,
+    /// for at most `max_steps` steps.
+    ///
+    /// The state of every wire and gate is compared after each step with all the states
+    /// seen so far. A fixed point is confirmed by one more step, and an oscillation is
+    /// simulated for one more period to find the wires that change.
+    ///
+    /// With [`TimingModel::Random`](crate::TimingModel::Random), gates that are still ramping
+    /// rarely repeat an earlier state exactly, so oscillations may end up `Unsettled`.
+    pub fn simulate_until_stable(&mut self, max_steps: u32) -> Stability {
+        self.simulate_until_stable_with(max_steps, |_| {})
+    }
+
+    /// Like [`Simulator::simulate_until_stable`], calling `on_step` after every step,
+    /// including those simulated to confirm the outcome.
+    pub fn simulate_until_stable_with(
+        &mut self,
+        max_steps: u32,
+        mut on_step: impl FnMut(&Simulator),
+    ) -> Stability {
+        let mut seen: HashMap<u64, u32> = HashMap::new();
+        seen.insert(self.state_hash(), 0);
+
+        for step in 1..=max_steps {
+            self.simulate_one_step();
+            on_step(self);
+
+            let hash = self.state_hash();
+            if let Some(&first_step) = seen.get(&hash) {
+                let period = step - first_step;
+
+                if period == 1 {
+                    return Stability::Stable { steps: first_step };
+                }
+
+                return Stability::Oscillating {
+                    steps: first_step,
+                    period,
+                    wire_ids: self.changing_wires(period, &mut on_step),
+                };
+            }
+
+            seen.insert(hash, step);
+        }
+
+        Stability::Unsettled { steps: max_steps }
+    }
+
+    /// Hash of everything that decides the next steps.
+    fn state_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+
+        for wire in &self.wires {
+            wire.state.hash(&mut hasher);
+        }
+        for gate in &self.gates {
+            gate.state.hash(&mut hasher);
+            gate.slow_state.to_bits().hash(&mut hasher);
+        }
+
+        hasher.finish()
+    }
+
+    /// Simulate `steps` steps and collect the wires whose state changes.
+    fn changing_wires(&mut self, steps: u32, on_step: &mut impl FnMut(&Simulator)) -> Vec<u32> {
+        let initial: Vec<bool> = self.wires.iter().map(|wire| wire.state).collect();
+        let mut changed = vec![false; self.wires.len()];
+
+        for _ in 0..steps {
+            self.simulate_one_step();
+            on_step(self);
+
+            for ((changed, wire), &initial) in changed.iter_mut().zip(&self.wires).zip(&initial) {
+                *changed |= wire.state != initial;
+            }
+        }
+
+        changed
+            .iter()
+            .enumerate()
+            .filter(|&(_, &changed)| changed)
+            .map(|(wire_id, _)| wire_id as u32)
+            .collect()
+    }
+}
@@ -0,0 +1,492 @@
+//! Scripted test benches.
+//!
+//! A test bench is a text file with one command per line, `#` starts a comment.
+//! Targets are named pins or buses, or `X,Y` coordinates of a pixel on a wire.
+//! Values are `0` or `1` for pins and pixels, and integers for buses, written in
+//! decimal, `0x` hexadecimal or `0b` binary. Negative values are two's complement.
+//!
+//! ```text
+//! set a 1            # drive a target
+//! set 12,34 0
+//! step 10            # simulate 10 steps
+//! settle             # simulate until stable, failing if it oscillates
+//! settle 50          # same, failing if it takes more than 50 steps
+//! timeout 5000       # steps `settle` and tables wait by default (1000)
+//! expect sum 1       # check a target
+//!
+//! table a b -> sum carry
+//! 0 0 -> 0 0         # set the inputs, settle, then check the outputs
+//! 0 1 -> 1 0
+//! 1 0 -> 1 0
+//! 1 1 -> 0 1
+//! end
+//! ```
+//!
+//! The bench stops at the first failure, reporting its line, step and pixel.
+
+use std::fmt;
+
+use crate::{Simulator, Stability, WireBus};
+
+const DEFAULT_TIMEOUT: u32 = 1000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Target {
+    Pixel(u32, u32),
+    Name(String),
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pixel(x, y) => write!(f, "({}, {})", x, y),
+            Self::Name(name) => f.write_str(name),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct TableRow {
+    line: usize,
+    inputs: Vec<i64>,
+    outputs: Vec<i64>,
+}
+
+#[derive(Clone, Debug)]
+enum Command {
+    Set(Target, i64),
+    Step(u32),
+    Settle(Option<u32>),
+    Timeout(u32),
+    Expect(Target, i64),
+    Table {
+        inputs: Vec<Target>,
+        outputs: Vec<Target>,
+        rows: Vec<TableRow>,
+    },
+}
+
+/// Invalid test bench, or a test bench referring to something the circuit lacks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScriptError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// First check of a test bench that did not hold.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Failure {
+    pub line: usize,
+    /// Step count of the simulator when the check failed.
+    pub step: u64,
+    /// Pixel of the mismatching wire, if the failure is about a single wire.
+    pub location: Option<(u32, u32)>,
+    pub message: String,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, step {}: {}",
+            self.line, self.step, self.message
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestReport {
+    /// Number of checks that held.
+    pub passed: usize,
+    pub failure: Option<Failure>,
+}
+
+impl TestReport {
+    pub fn is_pass(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TestBench {
+    commands: Vec<(usize, Command)>,
+}
+
+impl TestBench {
+    pub fn parse(text: &str) -> Result<Self, ScriptError> {
+        let mut commands = Vec::new();
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(line_no, line)| (line_no + 1, line.split('#').next().unwrap_or("").trim()))
+            .filter(|(_, line)| !line.is_empty());
+
+        while let Some((line, text)) = lines.next() {
+            let error = |message: String| ScriptError { line, message };
+            let words: Vec<&str> = text.split_whitespace().collect();
+
+            let command = match words.as_slice() {
+                ["set", target, value] => Command::Set(
+                    parse_target(target).map_err(error)?,
+                    parse_value(value).map_err(error)?,
+                ),
+                ["step", steps] => Command::Step(parse_steps(steps).map_err(error)?),
+                ["settle"] => Command::Settle(None),
+                ["settle", steps] => Command::Settle(Some(parse_steps(steps).map_err(error)?)),
+                ["timeout", steps] => Command::Timeout(parse_steps(steps).map_err(error)?),
+                ["expect", target, value] => Command::Expect(
+                    parse_target(target).map_err(error)?,
+                    parse_value(value).map_err(error)?,
+                ),
+                ["table", header @ ..] => {
+                    let (inputs, outputs) = split_arrow(header)
+                        .ok_or_else(|| error("expected table INPUTS -> OUTPUTS".to_string()))?;
+                    let inputs = inputs
+                        .iter()
+                        .map(|target| parse_target(target))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(error)?;
+                    let outputs = outputs
+                        .iter()
+                        .map(|target| parse_target(target))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(error)?;
+
+                    let mut rows = Vec::new();
+                    loop {
+                        let (row_line, row) = lines
+                            .next()
+                            .ok_or_else(|| error("table is not closed by end".to_string()))?;
+                        if row == "end" {
+                            break;
+                        }
+
+                        rows.push(parse_row(row_line, row, inputs.len(), outputs.len())?);
+                    }
+
+                    Command::Table {
+                        inputs,
+                        outputs,
+                        rows,
+                    }
+                }
+                _ => return Err(error(format!("invalid command {:?}", text))),
+            };
+
+            commands.push((line, command));
+        }
+
+        Ok(Self { commands })
+    }
+
+    /// Run the test bench on `simulator` from its current state.
+    pub fn run(&self, simulator: &mut Simulator) -> Result<TestReport, ScriptError> {
+        let mut runner = Runner {
+            simulator,
+            timeout: DEFAULT_TIMEOUT,
+            passed: 0,
+        };
+
+        for (line, command) in &self.commands {
+            if let Err(failure) = runner.run(*line, command)? {
+                return Ok(TestReport {
+                    passed: runner.passed,
+                    failure: Some(failure),
+                });
+            }
+        }
+
+        Ok(TestReport {
+            passed: runner.passed,
+            failure: None,
+        })
+    }
+}
+
+struct Runner<'a> {
+    simulator: &'a mut Simulator,
+    timeout: u32,
+    passed: usize,
+}
+
+/// Outcome of a command that ran, `Err` if a check failed.
+type Checked = Result<(), Failure>;
+
+impl Runner<'_> {
+    fn run(&mut self, line: usize, command: &Command) -> Result<Checked, ScriptError> {
+        match command {
+            Command::Set(target, value) => self.set(line, target, *value)?,
+            Command::Step(steps) => self.simulator.simulate(*steps),
+            Command::Settle(steps) => return Ok(self.settle(line, steps.unwrap_or(self.timeout))),
+            Command::Timeout(steps) => self.timeout = *steps,
+            Command::Expect(target, value) => return self.expect(line, target, *value),
+            Command::Table {
+                inputs,
+                outputs,
+                rows,
+            } => {
+                for row in rows {
+                    for (target, &value) in inputs.iter().zip(&row.inputs) {
+                        self.set(row.line, target, value)?;
+                    }
+
+                    if let Err(failure) = self.settle(row.line, self.timeout) {
+                        return Ok(Err(failure));
+                    }
+
+                    for (target, &value) in outputs.iter().zip(&row.outputs) {
+                        if let Err(failure) = self.expect(row.line, target, value)? {
+                            return Ok(Err(failure));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(Ok(()))
+    }
+
+    fn set(&mut self, line: usize, target: &Target, value: i64) -> Result<(), ScriptError> {
+        let done = match self.resolve(line, target)? {
+            Resolved::Wire { wire_id, .. } => {
+                let state = bit_value(line, target, value)?;
+                self.simulator.set_wire(wire_id, state);
+                true
+            }
+            Resolved::Bus { bus, .. } => {
+                if value < 0 {
+                    self.simulator.write_bus_signed(&bus, value)
+                } else {
+                    self.simulator.write_bus(&bus, value as u64)
+                }
+            }
+        };
+
+        if !done {
+            return Err(ScriptError {
+                line,
+                message: format!("{} does not fit in {}", value, target),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn settle(&mut self, line: usize, max_steps: u32) -> Checked {
+        let message = match self.simulator.simulate_until_stable(max_steps) {
+            Stability::Stable { .. } => return Ok(()),
+            Stability::Oscillating {
+                period, wire_ids, ..
+            } => format!(
+                "circuit oscillates with period {} on {} wires",
+                period,
+                wire_ids.len()
+            ),
+            Stability::Unsettled { steps } => {
+                format!("circuit is not stable after {} steps", steps)
+            }
+        };
+
+        Err(Failure {
+            line,
+            step: self.simulator.step_count(),
+            location: None,
+            message,
+        })
+    }
+
+    fn expect(
+        &mut self,
+        line: usize,
+        target: &Target,
+        expected: i64,
+    ) -> Result<Checked, ScriptError> {
+        let step = self.simulator.step_count();
+
+        let (actual, location) = match self.resolve(line, target)? {
+            Resolved::Wire { wire_id, x, y } => {
+                bit_value(line, target, expected)?;
+                let actual = self.simulator.get_wire_state(wire_id) as i64;
+                (actual, (x, y))
+            }
+            Resolved::Bus { bus, pixels } => {
+                let actual = if expected < 0 {
+                    self.simulator.read_bus_signed(&bus)
+                } else {
+                    self.simulator.read_bus(&bus) as i64
+                };
+
+                // Point at the least significant bit that differs.
+                let mismatch = (0..pixels.len())
+                    .find(|&bit| (actual ^ expected) >> bit & 1 == 1)
+                    .unwrap_or(0);
+                (actual, pixels[mismatch])
+            }
+        };
+
+        if actual == expected {
+            self.passed += 1;
+            return Ok(Ok(()));
+        }
+
+        Ok(Err(Failure {
+            line,
+            step,
+            location: Some(location),
+            message: format!(
+                "{} at ({}, {}) is {}, expected {}",
+                target, location.0, location.1, actual, expected
+            ),
+        }))
+    }
+
+    fn resolve(&self, line: usize, target: &Target) -> Result<Resolved, ScriptError> {
+        let error = |message: String| ScriptError { line, message };
+
+        match target {
+            Target::Pixel(x, y) => {
+                let wire_id = self
+                    .simulator
+                    .wire_id_at(*x, *y)
+                    .ok_or_else(|| error(format!("{} is not on a wire", target)))?;
+                Ok(Resolved::Wire {
+                    wire_id,
+                    x: *x,
+                    y: *y,
+                })
+            }
+            Target::Name(name) => {
+                let pins = self.simulator.pins();
+
+                if let Some(pin) = pins.pin(name) {
+                    Ok(Resolved::Wire {
+                        wire_id: pin.wire_id,
+                        x: pin.x,
+                        y: pin.y,
+                    })
+                } else if let Some(bus) = pins.bus(name) {
+                    Ok(Resolved::Bus {
+                        bus: bus.wire_bus(),
+                        pixels: bus.bits.iter().map(|bit| (bit.x, bit.y)).collect(),
+                    })
+                } else {
+                    Err(error(format!("no pin or bus named {}", name)))
+                }
+            }
+        }
+    }
+}
+
+enum Resolved {
+    Wire {
+        wire_id: u32,
+        x: u32,
+        y: u32,
+    },
+    /// A named bus with the pixel of every bit, least significant first.
+    Bus {
+        bus: WireBus,
+        pixels: Vec<(u32, u32)>,
+    },
+}
+
+fn bit_value(line: usize, target: &Target, value: i64) -> Result<bool, ScriptError> {
+    match value {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(ScriptError {
+            line,
+            message: format!("{} is a single wire, expected 0 or 1", target),
+        }),
+    }
+}
+
+fn parse_target(text: &str) -> Result<Target, String> {
+    match text.split_once(',') {
+        Some((x, y)) => {
+            let coord = |value: &str| {
+                value
+                    .parse()
+                    .map_err(|_| format!("invalid coordinates {:?}", text))
+            };
+            Ok(Target::Pixel(coord(x)?, coord(y)?))
+        }
+        None => Ok(Target::Name(text.to_string())),
+    }
+}
+
+fn parse_value(text: &str) -> Result<i64, String> {
+    let invalid = || format!("invalid value {:?}", text);
+
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+    let magnitude = if let Some(hex) = digits.strip_prefix("0x") {
+        u64::from_str_radix(hex, 16)
+    } else if let Some(binary) = digits.strip_prefix("0b") {
+        u64::from_str_radix(binary, 2)
+    } else {
+        digits.parse()
+    }
+    .map_err(|_| invalid())?;
+
+    if negative {
+        0i64.checked_sub_unsigned(magnitude).ok_or_else(invalid)
+    } else {
+        // Values above i64::MAX are kept as their 64-bit pattern.
+        Ok(magnitude as i64)
+    }
+}
+
+fn parse_steps(text: &str) -> Result<u32, String> {
+    text.parse()
+        .map_err(|_| format!("invalid number of steps {:?}", text))
+}
+
+/// Split `a b -> c d` at the arrow.
+fn split_arrow<'a, 'b>(words: &'b [&'a str]) -> Option<(&'b [&'a str], &'b [&'a str])> {
+    let arrow = words.iter().position(|&word| word == "->")?;
+    Some((&words[..arrow], &words[arrow + 1..]))
+}
+
+fn parse_row(
+    line: usize,
+    text: &str,
+    input_count: usize,
+    output_count: usize,
+) -> Result<TableRow, ScriptError> {
+    let error = |message: String| ScriptError { line, message };
+    let words: Vec<&str> = text.split_whitespace().collect();
+
+    let (inputs, outputs) =
+        split_arrow(&words).ok_or_else(|| error("expected INPUTS -> OUTPUTS".to_string()))?;
+    if inputs.len() != input_count || outputs.len() != output_count {
+        return Err(error(format!(
+            "expected {} inputs and {} outputs",
+            input_count, output_count
+        )));
+    }
+
+    let parse_values = |values: &[&str]| {
+        values
+            .iter()
+            .map(|value| parse_value(value))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(error)
+    };
+
+    Ok(TableRow {
+        line,
+        inputs: parse_values(inputs)?,
+        outputs: parse_values(outputs)?,
+    })
+}
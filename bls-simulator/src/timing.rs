@@ -0,0 +1,52 @@
+use rand::Rng;
+
+/// How fast a [`NotGate`](crate::NotGate) output follows its input.
+///
+/// Every gate keeps a `slow_state` between `0.0` and `1.0`. Each update moves it
+/// towards the target state by the amount returned from [`TimingModel::delta`],
+/// and the output only flips once it reaches the end of the range.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimingModel {
+    /// Gates switch instantly and each step propagates until the circuit settles.
+    Ideal,
+    /// Every gate switches exactly one step after its input changes.
+    Unit,
+    /// Gates ramp by `rise` (or `fall`) plus a random fraction of `jitter` per step,
+    /// which mimics real world races and glitches.
+    Random { rise: f32, fall: f32, jitter: f32 },
+    /// Gates switch after a fixed number of steps, separately for rising and falling outputs.
+    /// Delays below one step are treated as one step.
+    RiseFall { rise: u32, fall: u32 },
+}
+
+impl Default for TimingModel {
+    fn default() -> Self {
+        Self::Random {
+            rise: 0.5,
+            fall: 0.5,
+            jitter: 0.5,
+        }
+    }
+}
+
+impl TimingModel {
+    /// Amount `slow_state` moves in a single update towards a rising or falling output.
+    pub fn delta<R: Rng>(&self, rising: bool, rng: &mut R) -> f32 {
+        match *self {
+            Self::Ideal | Self::Unit => 1.0,
+            Self::Random { rise, fall, jitter } => {
+                let base = if rising { rise } else { fall };
+                base + jitter * rng.gen::<f32>()
+            }
+            Self::RiseFall { rise, fall } => {
+                let steps = if rising { rise } else { fall };
+                if steps <= 1 {
+                    1.0
+                } else {
+                    // Slightly more than 1/steps, so rounding never costs an extra step.
+                    1.0 / (steps as f32 - 0.5)
+                }
+            }
+        }
+    }
+}
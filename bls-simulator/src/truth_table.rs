@@ -0,0 +1,309 @@
+use std::{
+    collections::BTreeSet,
+    fmt,
+    io::{self, Write},
+};
+
+use crate::Simulator;
+
+/// At most 2^16 input combinations are enumerated.
+pub const TRUTH_TABLE_MAX_INPUTS: usize = 16;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TruthTableError {
+    UnknownPin(String),
+    TooManyInputs(usize),
+}
+
+impl fmt::Display for TruthTableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownPin(name) => write!(f, "no pin named {}", name),
+            Self::TooManyInputs(count) => write!(
+                f,
+                "{} inputs, at most {} are supported",
+                count, TRUTH_TABLE_MAX_INPUTS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TruthTableError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TruthTableRow {
+    /// Input values, the first input being the most significant bit.
+    pub inputs: u64,
+    /// Output values, `None` if the circuit did not settle for these inputs.
+    pub outputs: Option<Vec<bool>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TruthTable {
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+    /// One row per input combination, in counting order.
+    pub rows: Vec<TruthTableRow>,
+}
+
+impl Simulator {
+    /// Enumerate every combination of the named input pins, simulate until stable for
+    /// at most `max_steps` steps each, and record the named output pins.
+    ///
+    /// Combinations are applied in counting order, each starting from the state the
+    /// previous one left, so circuits with memory may depend on that order.
+    pub fn truth_table(
+        &mut self,
+        inputs: &[&str],
+        outputs: &[&str],
+        max_steps: u32,
+    ) -> Result<TruthTable, TruthTableError> {
+        if inputs.len() > TRUTH_TABLE_MAX_INPUTS {
+            return Err(TruthTableError::TooManyInputs(inputs.len()));
+        }
+        for name in inputs.iter().chain(outputs) {
+            if self.pins().pin(name).is_none() {
+                return Err(TruthTableError::UnknownPin(name.to_string()));
+            }
+        }
+
+        let mut rows = Vec::new();
+
+        for combination in 0..1u64 << inputs.len() {
+            for (i, name) in inputs.iter().enumerate() {
+                self.set_pin(name, input_bit(combination, i, inputs.len()));
+            }
+
+            let outputs = self.simulate_until_stable(max_steps).is_stable().then(|| {
+                outputs
+                    .iter()
+                    .map(|name| self.get_pin(name).unwrap_or(false))
+                    .collect()
+            });
+
+            rows.push(TruthTableRow {
+                inputs: combination,
+                outputs,
+            });
+        }
+
+        Ok(TruthTable {
+            inputs: inputs.iter().map(|name| name.to_string()).collect(),
+            outputs: outputs.iter().map(|name| name.to_string()).collect(),
+            rows,
+        })
+    }
+}
+
+/// Value of input `index` of `count` in `combination`.
+fn input_bit(combination: u64, index: usize, count: usize) -> bool {
+    combination >> (count - 1 - index) & 1 == 1
+}
+
+impl TruthTable {
+    /// Rows for which the circuit never settled.
+    pub fn unsettled(&self) -> impl Iterator<Item = &TruthTableRow> {
+        self.rows.iter().filter(|row| row.outputs.is_none())
+    }
+
+    /// Write the table with a column per pin, `-` for outputs that never settled.
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let header: Vec<&str> = self.inputs.iter().map(String::as_str).collect();
+        let outputs: Vec<&str> = self.outputs.iter().map(String::as_str).collect();
+        writeln!(out, "{} | {}", header.join(" "), outputs.join(" "))?;
+
+        for row in &self.rows {
+            let inputs: Vec<String> = self
+                .inputs
+                .iter()
+                .enumerate()
+                .map(|(i, name)| {
+                    let value = input_bit(row.inputs, i, self.inputs.len()) as u8;
+                    format!("{:<width$}", value, width = name.len())
+                })
+                .collect();
+
+            let outputs: Vec<String> = self
+                .outputs
+                .iter()
+                .enumerate()
+                .map(|(i, name)| {
+                    let value = match &row.outputs {
+                        Some(values) => (values[i] as u8).to_string(),
+                        None => "-".to_string(),
+                    };
+                    format!("{:<width$}", value, width = name.len())
+                })
+                .collect();
+
+            let line = format!("{} | {}", inputs.join(" "), outputs.join(" "));
+            if row.outputs.is_some() {
+                writeln!(out, "{}", line.trim_end())?;
+            } else {
+                writeln!(out, "{}  # never settles", line.trim_end())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Minimal sum of products of output `index`, such as `a & !b | !a & b`.
+    ///
+    /// Combinations that never settle are treated as don't cares.
+    pub fn minimized(&self, index: usize) -> String {
+        let mut ones = Vec::new();
+        let mut dont_cares = Vec::new();
+
+        for row in &self.rows {
+            match &row.outputs {
+                Some(values) if values[index] => ones.push(row.inputs),
+                Some(_) => {}
+                None => dont_cares.push(row.inputs),
+            }
+        }
+
+        let implicants = minimize(self.inputs.len(), &ones, &dont_cares);
+
+        if implicants.is_empty() {
+            return "0".to_string();
+        }
+        if implicants
+            .iter()
+            .any(|implicant| implicant.mask == all_bits(self.inputs.len()))
+        {
+            return "1".to_string();
+        }
+
+        let terms: Vec<String> = implicants
+            .iter()
+            .map(|implicant| {
+                let literals: Vec<String> = self
+                    .inputs
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, _)| !input_bit(implicant.mask, i, self.inputs.len()))
+                    .map(|(i, name)| {
+                        if input_bit(implicant.value, i, self.inputs.len()) {
+                            name.clone()
+                        } else {
+                            format!("!{}", name)
+                        }
+                    })
+                    .collect();
+                literals.join(" & ")
+            })
+            .collect();
+
+        terms.join(" | ")
+    }
+}
+
+fn all_bits(count: usize) -> u64 {
+    (1u64 << count) - 1
+}
+
+/// A product term: inputs set in `mask` are free, the others must equal `value`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Implicant {
+    mask: u64,
+    value: u64,
+}
+
+impl Implicant {
+    fn covers(self, combination: u64) -> bool {
+        combination & !self.mask == self.value
+    }
+
+    fn literal_count(self) -> u32 {
+        (!self.mask).count_ones()
+    }
+}
+
+/// Quine-McCluskey: find the prime implicants, then cover `ones` with the essential
+/// ones first and greedily with the largest remaining ones.
+fn minimize(input_count: usize, ones: &[u64], dont_cares: &[u64]) -> Vec<Implicant> {
+    if ones.is_empty() {
+        return Vec::new();
+    }
+
+    let mut current: BTreeSet<Implicant> = ones
+        .iter()
+        .chain(dont_cares)
+        .map(|&value| Implicant { mask: 0, value })
+        .collect();
+    let mut primes: Vec<Implicant> = Vec::new();
+
+    while !current.is_empty() {
+        let mut next = BTreeSet::new();
+        let mut combined = BTreeSet::new();
+
+        for &a in &current {
+            for bit in 0..input_count {
+                let bit = 1u64 << bit;
+                if a.mask & bit != 0 || a.value & bit != 0 {
+                    continue;
+                }
+
+                let b = Implicant {
+                    mask: a.mask,
+                    value: a.value | bit,
+                };
+                if current.contains(&b) {
+                    next.insert(Implicant {
+                        mask: a.mask | bit,
+                        value: a.value,
+                    });
+                    combined.insert(a);
+                    combined.insert(b);
+                }
+            }
+        }
+
+        primes.extend(current.difference(&combined));
+        current = next;
+    }
+
+    let mut uncovered: Vec<u64> = ones.to_vec();
+    let mut cover: Vec<Implicant> = Vec::new();
+
+    for &one in ones {
+        let mut covering = primes.iter().filter(|prime| prime.covers(one));
+        if let (Some(&prime), None) = (covering.next(), covering.next()) {
+            if !cover.contains(&prime) {
+                cover.push(prime);
+            }
+        }
+    }
+    uncovered.retain(|&one| !cover.iter().any(|prime| prime.covers(one)));
+
+    while !uncovered.is_empty() {
+        let best = *primes
+            .iter()
+            .max_by_key(|prime| {
+                let count = uncovered.iter().filter(|&&one| prime.covers(one)).count();
+                (count, std::cmp::Reverse(prime.literal_count()))
+            })
+            .unwrap();
+
+        cover.push(best);
+        uncovered.retain(|&one| !best.covers(one));
+    }
+
+    // Most significant inputs first, negated before plain.
+    cover.sort_by_key(|implicant| {
+        (0..input_count)
+            .rev()
+            .map(|bit| {
+                let bit = 1u64 << bit;
+                if implicant.mask & bit != 0 {
+                    2
+                } else if implicant.value & bit != 0 {
+                    1
+                } else {
+                    0
+                }
+            })
+            .collect::<Vec<_>>()
+    });
+    cover
+}
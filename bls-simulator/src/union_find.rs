@@ -14,8 +14,8 @@ impl UnionFind<i32> for Vec<i32> {
             return i;
         }
 
-        self[i as usize] = self.find(self[i as usize]) as i32;
-        return self[i as usize];
+        self[i as usize] = self.find(self[i as usize]);
+        self[i as usize]
     }
 
     fn merge(&mut self, a: i32, b: i32) -> bool {
@@ -30,9 +30,9 @@ impl UnionFind<i32> for Vec<i32> {
             return false;
         }
 
-        self[a_parent as usize] = b_parent as i32;
+        self[a_parent as usize] = b_parent;
 
-        return true;
+        true
     }
 
     fn is_root(&mut self, i: i32) -> bool {
@@ -0,0 +1,205 @@
+use std::io::{self, Write};
+
+use crate::Simulator;
+
+/// A named wire whose state is recorded.
+pub struct WireProbe {
+    pub name: String,
+    /// Pixel the probe was put on, to find its wire again after the circuit changed.
+    pub x: u32,
+    pub y: u32,
+    pub wire_id: u32,
+}
+
+struct Change {
+    step: u64,
+    probe_id: usize,
+    state: bool,
+}
+
+/// Records state changes of probed wires and writes them as a Value Change Dump.
+#[derive(Default)]
+pub struct VcdRecorder {
+    probes: Vec<WireProbe>,
+    last_states: Vec<bool>,
+    changes: Vec<Change>,
+}
+
+impl VcdRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn probes(&self) -> &[WireProbe] {
+        &self.probes
+    }
+
+    /// Start recording the wire of `probe`, sampling its current state at `step`.
+    /// `wire_state` gives the state of a wire by id.
+    pub fn add_probe(&mut self, probe: WireProbe, step: u64, wire_state: impl Fn(u32) -> bool) {
+        let state = wire_state(probe.wire_id);
+
+        self.changes.push(Change {
+            step,
+            probe_id: self.probes.len(),
+            state,
+        });
+        self.probes.push(probe);
+        self.last_states.push(state);
+    }
+
+    /// Record every probed wire whose state changed since the last sample.
+    pub fn sample(&mut self, step: u64, wire_state: impl Fn(u32) -> bool) {
+        for (probe_id, probe) in self.probes.iter().enumerate() {
+            let state = wire_state(probe.wire_id);
+
+            if state != self.last_states[probe_id] {
+                self.last_states[probe_id] = state;
+                self.changes.push(Change {
+                    step,
+                    probe_id,
+                    state,
+                });
+            }
+        }
+    }
+
+    /// Forget the changes after `step`, when the simulation takes another course from
+    /// there. Probes added after `step` start over from the state of their wire.
+    pub(crate) fn truncate(&mut self, step: u64, wire_state: impl Fn(u32) -> bool) {
+        self.changes.retain(|change| change.step <= step);
+
+        let mut last_states = vec![None; self.probes.len()];
+        for change in &self.changes {
+            last_states[change.probe_id] = Some(change.state);
+        }
+
+        for (probe_id, (probe, last_state)) in self.probes.iter().zip(last_states).enumerate() {
+            self.last_states[probe_id] = match last_state {
+                Some(state) => state,
+                None => {
+                    let state = wire_state(probe.wire_id);
+                    self.changes.push(Change {
+                        step,
+                        probe_id,
+                        state,
+                    });
+                    state
+                }
+            };
+        }
+    }
+
+    /// Find the wire of every probe again after the circuit was extracted again.
+    /// Probes no longer on a wire are dropped along with their recording.
+    pub(crate) fn relocate(&mut self, simulator: &Simulator) {
+        let mut probe_ids = Vec::with_capacity(self.probes.len());
+        let mut probes = Vec::new();
+        let mut last_states = Vec::new();
+
+        for (probe, &last_state) in self.probes.drain(..).zip(&self.last_states) {
+            match simulator.wire_id_at(probe.x, probe.y) {
+                Some(wire_id) => {
+                    probe_ids.push(Some(probes.len()));
+                    probes.push(WireProbe { wire_id, ..probe });
+                    last_states.push(last_state);
+                }
+                None => probe_ids.push(None),
+            }
+        }
+
+        self.probes = probes;
+        self.last_states = last_states;
+        self.changes
+            .retain_mut(|change| match probe_ids[change.probe_id] {
+                Some(probe_id) => {
+                    change.probe_id = probe_id;
+                    true
+                }
+                None => false,
+            });
+    }
+
+    /// Write the recording in VCD format, one time unit per simulation step.
+    /// `end_step` is the last step the waveform should extend to.
+    pub fn write<W: Write>(&self, out: &mut W, end_step: u64) -> io::Result<()> {
+        writeln!(out, "$version bls-rs $end")?;
+        writeln!(out, "$timescale 1ns $end")?;
+        writeln!(out, "$scope module circuit $end")?;
+        for (probe_id, probe) in self.probes.iter().enumerate() {
+            writeln!(
+                out,
+                "$var wire 1 {} {} $end",
+                vcd_identifier(probe_id),
+                vcd_name(&probe.name)
+            )?;
+        }
+        writeln!(out, "$upscope $end")?;
+        writeln!(out, "$enddefinitions $end")?;
+
+        let start_step = self.changes.first().map_or(0, |change| change.step);
+        let mut changes = self.changes.iter().peekable();
+
+        // Probes added later than the first one are unknown until their first sample.
+        let mut initial_states = vec![None; self.probes.len()];
+        while let Some(change) = changes.next_if(|change| change.step == start_step) {
+            initial_states[change.probe_id] = Some(change.state);
+        }
+
+        writeln!(out, "#{}", start_step)?;
+        writeln!(out, "$dumpvars")?;
+        for (probe_id, state) in initial_states.iter().enumerate() {
+            let value = match state {
+                Some(true) => '1',
+                Some(false) => '0',
+                None => 'x',
+            };
+            writeln!(out, "{}{}", value, vcd_identifier(probe_id))?;
+        }
+        writeln!(out, "$end")?;
+
+        let mut time = start_step;
+        for change in changes {
+            if change.step != time {
+                time = change.step;
+                writeln!(out, "#{}", time)?;
+            }
+            writeln!(
+                out,
+                "{}{}",
+                change.state as u8,
+                vcd_identifier(change.probe_id)
+            )?;
+        }
+
+        if end_step > time {
+            writeln!(out, "#{}", end_step)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Short identifier code made of printable ASCII characters `!` to `~`.
+fn vcd_identifier(mut index: usize) -> String {
+    const FIRST: u8 = b'!';
+    const COUNT: usize = (b'~' - b'!' + 1) as usize;
+
+    let mut identifier = String::new();
+    loop {
+        identifier.push((FIRST + (index % COUNT) as u8) as char);
+        index /= COUNT;
+        if index == 0 {
+            break;
+        }
+        index -= 1;
+    }
+    identifier
+}
+
+/// VCD names are whitespace separated, so whitespace is replaced.
+fn vcd_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_whitespace() { '_' } else { c })
+        .collect()
+}
@@ -0,0 +1,225 @@
+use std::{
+    collections::HashSet,
+    io::{self, Write},
+};
+
+use serde::Deserialize;
+
+use crate::Netlist;
+
+/// Prefix of the nets of wires and gate outputs, kept apart from port names.
+const NET_PREFIX: &str = "_n_";
+
+/// Reserved words of Verilog-2005, which need escaping as identifiers.
+const KEYWORDS: &str = "\
+    always and assign automatic begin buf bufif0 bufif1 case casex casez cell cmos config \
+    deassign default defparam design disable edge else end endcase endconfig endfunction \
+    endgenerate endmodule endprimitive endspecify endtable endtask event for force forever \
+    fork function generate genvar highz0 highz1 if ifnone incdir include initial inout input \
+    instance integer join large liblist library localparam macromodule medium module nand \
+    negedge nmos nor noshowcancelled not notif0 notif1 or output parameter pmos posedge \
+    primitive pull0 pull1 pulldown pullup pulsestyle_ondetect pulsestyle_onevent rcmos real \
+    realtime reg release repeat rnmos rpmos rtran rtranif0 rtranif1 scalared showcancelled \
+    signed small specify specparam strong0 strong1 supply0 supply1 table task time tran \
+    tranif0 tranif1 tri tri0 tri1 triand trior trireg unsigned use uwire vectored wait wand \
+    weak0 weak1 while wire wor xnor xor";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PortDirection {
+    Input,
+    Output,
+}
+
+/// A wire exposed as a port of the exported module.
+#[derive(Clone, Debug)]
+pub struct VerilogPort {
+    pub name: String,
+    pub wire_id: u32,
+    pub direction: PortDirection,
+}
+
+#[derive(Clone, Debug)]
+pub struct VerilogOptions {
+    pub module_name: String,
+    pub ports: Vec<VerilogPort>,
+    /// Delay of every inverter, `0` for none.
+    /// A delay keeps feedback loops such as latches and oscillators from racing.
+    pub gate_delay: u32,
+}
+
+impl Default for VerilogOptions {
+    fn default() -> Self {
+        Self {
+            module_name: "circuit".to_string(),
+            ports: Vec::new(),
+            gate_delay: 1,
+        }
+    }
+}
+
+impl Netlist {
+    /// Write the netlist as a structural Verilog module.
+    ///
+    /// Wire `N` becomes net `_n_wN` and the output of gate `N` becomes net `_n_gN`.
+    /// Each gate is a `not` primitive, and a wire is the OR of all gates driving it,
+    /// like [`Simulator::get_wire_state`](crate::Simulator::get_wire_state).
+    /// Wires without drivers are tied low unless they are input ports, and are the OR of
+    /// their input ports if there are several, as if each one drove the wire.
+    ///
+    /// Port names are made valid identifiers, and Verilog keywords are escaped.
+    /// Fails if an input port is attached to a wire that is driven by gates, or if two
+    /// ports, or a port and an internal net, end up with the same name.
+    pub fn write_verilog<W: Write>(&self, out: &mut W, options: &VerilogOptions) -> io::Result<()> {
+        let port_names: Vec<String> = options
+            .ports
+            .iter()
+            .map(|port| verilog_identifier(&port.name))
+            .collect();
+        let mut input_ports: Vec<Vec<&str>> = vec![Vec::new(); self.wires.len()];
+
+        for (port, name) in options.ports.iter().zip(&port_names) {
+            let wire = self.wires.get(port.wire_id as usize).ok_or_else(|| {
+                invalid_input(format!("port {} refers to unknown wire", port.name))
+            })?;
+
+            if port.direction == PortDirection::Input {
+                if !wire.drivers.is_empty() {
+                    return Err(invalid_input(format!(
+                        "input port {} is driven by gates",
+                        port.name
+                    )));
+                }
+                input_ports[port.wire_id as usize].push(name);
+            }
+        }
+
+        let mut seen = HashSet::new();
+        for (port, name) in options.ports.iter().zip(&port_names) {
+            if is_internal_net(name) {
+                return Err(invalid_input(format!(
+                    "port {} clashes with an internal net",
+                    port.name
+                )));
+            }
+            if !seen.insert(name) {
+                return Err(invalid_input(format!(
+                    "port {} is named {} like another port",
+                    port.name, name
+                )));
+            }
+        }
+
+        writeln!(
+            out,
+            "module {}({});",
+            verilog_identifier(&options.module_name),
+            port_names.join(", ")
+        )?;
+
+        for (port, name) in options.ports.iter().zip(&port_names) {
+            let direction = match port.direction {
+                PortDirection::Input => "input",
+                PortDirection::Output => "output",
+            };
+            writeln!(out, "    {} {};", direction, name)?;
+        }
+        writeln!(out)?;
+
+        for wire in &self.wires {
+            writeln!(out, "    wire {}w{};", NET_PREFIX, wire.id)?;
+        }
+        for gate in &self.gates {
+            writeln!(out, "    wire {}g{};", NET_PREFIX, gate.id)?;
+        }
+        writeln!(out)?;
+
+        let delay = if options.gate_delay > 0 {
+            format!(" #{}", options.gate_delay)
+        } else {
+            String::new()
+        };
+        for gate in &self.gates {
+            writeln!(
+                out,
+                "    not{} not{} ({p}g{}, {p}w{}); // ({}, {}) {}",
+                delay,
+                gate.id,
+                gate.id,
+                gate.input,
+                gate.x,
+                gate.y,
+                gate.direction,
+                p = NET_PREFIX
+            )?;
+        }
+        writeln!(out)?;
+
+        for wire in &self.wires {
+            let value = if !wire.drivers.is_empty() {
+                wire.drivers
+                    .iter()
+                    .map(|gate_id| format!("{}g{}", NET_PREFIX, gate_id))
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            } else if !input_ports[wire.id as usize].is_empty() {
+                input_ports[wire.id as usize].join(" | ")
+            } else {
+                "1'b0".to_string()
+            };
+            writeln!(out, "    assign {}w{} = {};", NET_PREFIX, wire.id, value)?;
+        }
+
+        for (port, name) in options.ports.iter().zip(&port_names) {
+            if port.direction == PortDirection::Output {
+                writeln!(
+                    out,
+                    "    assign {} = {}w{};",
+                    name, NET_PREFIX, port.wire_id
+                )?;
+            }
+        }
+
+        writeln!(out, "endmodule")
+    }
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Whether `name` is the name of the net of a wire or a gate output.
+fn is_internal_net(name: &str) -> bool {
+    name.strip_prefix(NET_PREFIX)
+        .and_then(|name| name.strip_prefix(['w', 'g']))
+        .is_some_and(|id| !id.is_empty() && id.bytes().all(|c| c.is_ascii_digit()))
+}
+
+/// Replace characters not allowed in a simple Verilog identifier,
+/// and escape keywords.
+fn verilog_identifier(name: &str) -> String {
+    let mut identifier: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if !identifier.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        identifier.insert(0, '_');
+    }
+
+    if KEYWORDS
+        .split_whitespace()
+        .any(|keyword| keyword == identifier)
+    {
+        // Escaped identifiers end at the next whitespace.
+        return format!("\\{} ", identifier);
+    }
+
+    identifier
+}
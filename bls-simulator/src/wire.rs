@@ -0,0 +1,13 @@
+#[derive(Default)]
+pub struct Wire {
+    pub gate_in_ids: Vec<u32>,
+    pub gate_out_ids: Vec<u32>,
+
+    pub state: bool,
+}
+
+impl Wire {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
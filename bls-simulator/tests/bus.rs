@@ -0,0 +1,119 @@
+//! Multi-bit values written to and read from groups of wires.
+
+mod common;
+
+use bls_simulator::*;
+use proptest::prelude::*;
+
+const STEPS: u32 = 50;
+
+/// One horizontal wire per row, without any gate.
+fn wires(count: u32) -> Simulator {
+    let data: Vec<u8> = (0..count * 2)
+        .flat_map(|y| {
+            let value = if y % 2 == 0 { 255 } else { 0 };
+            [[value, value, value, 255]; 3]
+        })
+        .flatten()
+        .collect();
+
+    Simulator::from_pixels(3, count * 2, 4, &data)
+}
+
+fn bus(simulator: &Simulator, count: u32, order: BitOrder) -> WireBus {
+    let wire_ids = (0..count)
+        .map(|i| simulator.wire_id_at(1, i * 2).unwrap())
+        .collect();
+
+    WireBus::new(wire_ids, order)
+}
+
+#[test]
+fn bit_order() {
+    let mut simulator = wires(4);
+    let lsb_first = bus(&simulator, 4, BitOrder::LsbFirst);
+    let msb_first = bus(&simulator, 4, BitOrder::MsbFirst);
+
+    assert!(simulator.write_bus(&lsb_first, 0b0011));
+
+    // the first row holds the least significant bit
+    assert_eq!(simulator.get(0, 0), Some(true));
+    assert_eq!(simulator.get(0, 6), Some(false));
+    assert_eq!(simulator.read_bus(&lsb_first), 0b0011);
+    assert_eq!(simulator.read_bus(&msb_first), 0b1100);
+}
+
+#[test]
+fn values_that_do_not_fit_leave_the_wires_untouched() {
+    let mut simulator = wires(4);
+    let bus = bus(&simulator, 4, BitOrder::LsbFirst);
+
+    assert!(simulator.write_bus(&bus, 5));
+    assert!(!simulator.write_bus(&bus, 16));
+    assert!(!simulator.write_bus_signed(&bus, 8));
+    assert!(!simulator.write_bus_signed(&bus, -9));
+    assert_eq!(simulator.read_bus(&bus), 5);
+
+    assert!(simulator.write_bus_signed(&bus, -8));
+    assert_eq!(simulator.read_bus(&bus), 0b1000);
+    assert_eq!(simulator.read_bus_signed(&bus), -8);
+}
+
+#[test]
+fn full_width_bus() {
+    let mut simulator = wires(64);
+    let bus = bus(&simulator, 64, BitOrder::LsbFirst);
+
+    assert!(simulator.write_bus(&bus, u64::MAX));
+    assert_eq!(simulator.read_bus(&bus), u64::MAX);
+    assert_eq!(simulator.read_bus_signed(&bus), -1);
+
+    assert!(simulator.write_bus_signed(&bus, i64::MIN));
+    assert_eq!(simulator.read_bus_signed(&bus), i64::MIN);
+}
+
+#[test]
+fn named_buses() {
+    let mut simulator = common::load_with_pins("half_adder");
+
+    for operands in 0..4u64 {
+        assert!(simulator.set_bus("operands", operands));
+        simulator.simulate(STEPS);
+
+        let sum = (operands & 1) + (operands >> 1);
+        assert_eq!(simulator.get_bus("result"), Some(sum));
+        assert_eq!(
+            simulator.get_bus_signed("result"),
+            Some(if sum == 2 { -2 } else { sum as i64 })
+        );
+    }
+
+    assert!(simulator.set_bus_signed("operands", -1));
+    assert!(!simulator.set_bus("operands", 4));
+    assert!(!simulator.set_bus("missing", 0));
+    assert_eq!(simulator.get_bus("missing"), None);
+}
+
+proptest! {
+    #[test]
+    fn unsigned_round_trip(width in 1..=64u32, value: u64) {
+        let mut simulator = wires(width);
+        let bus = bus(&simulator, width, BitOrder::MsbFirst);
+        let fits = width == 64 || value < 1 << width;
+
+        prop_assert_eq!(simulator.write_bus(&bus, value), fits);
+        if fits {
+            prop_assert_eq!(simulator.read_bus(&bus), value);
+        }
+    }
+
+    #[test]
+    fn signed_round_trip(width in 1..=64u32, value: i64) {
+        let mut simulator = wires(width);
+        let bus = bus(&simulator, width, BitOrder::LsbFirst);
+        let value = value >> (64 - width);
+
+        prop_assert!(simulator.write_bus_signed(&bus, value));
+        prop_assert_eq!(simulator.read_bus_signed(&bus), value);
+    }
+}
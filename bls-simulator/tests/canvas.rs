@@ -0,0 +1,137 @@
+//! Drawing tools, undo and saving of the pixel editor canvas.
+
+use bls_simulator::*;
+
+const WIRE: Rgba = [255, 255, 255, 255];
+const EMPTY: Rgba = [0, 0, 0, 255];
+
+fn blank(width: u32, height: u32) -> Canvas {
+    Canvas::new(PixelBuffer {
+        width,
+        height,
+        components: 4,
+        data: EMPTY.repeat((width * height) as usize),
+    })
+}
+
+/// Rows of `#` (wire) and `.` (anything else).
+fn rows(canvas: &Canvas) -> Vec<String> {
+    (0..canvas.height())
+        .map(|y| {
+            (0..canvas.width())
+                .map(|x| {
+                    if canvas.get(x, y) == Some(WIRE) {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn lines_and_rectangles() {
+    let mut canvas = blank(6, 4);
+
+    assert_eq!(
+        canvas.line((0, 0), (5, 2), WIRE),
+        PixelRect::new(0, 0, 6, 3)
+    );
+    assert_eq!(rows(&canvas), ["##....", "..##..", "....##", "......"]);
+
+    let rect = canvas.fill_rect(PixelRect::from_corners((4, 3), (1, 1)), EMPTY);
+    assert_eq!(rect, PixelRect::new(1, 1, 4, 3));
+    assert_eq!(rows(&canvas), ["##....", "......", ".....#", "......"]);
+
+    // Drawing off the canvas is clipped.
+    assert_eq!(
+        canvas.fill_rect(PixelRect::new(5, 3, 10, 10), WIRE),
+        PixelRect::new(5, 3, 1, 1)
+    );
+    assert_eq!(canvas.get(5, 3), Some(WIRE));
+    assert_eq!(canvas.get(6, 3), None);
+}
+
+#[test]
+fn copy_and_paste() {
+    let mut canvas = blank(5, 3);
+    canvas.line((0, 0), (1, 1), WIRE);
+
+    let clip = canvas.copy(PixelRect::new(0, 0, 2, 2));
+    assert_eq!((clip.width, clip.height), (2, 2));
+
+    assert_eq!(canvas.paste(&clip, 3, 1), PixelRect::new(3, 1, 2, 2));
+    assert_eq!(rows(&canvas), ["#....", ".#.#.", "....#"]);
+
+    // Only the part inside the canvas is pasted.
+    assert_eq!(canvas.paste(&clip, 4, 2), PixelRect::new(4, 2, 1, 1));
+    assert_eq!(rows(&canvas), ["#....", ".#.#.", "....#"]);
+}
+
+#[test]
+fn strokes_are_undone_and_redone_at_once() {
+    let mut canvas = blank(4, 2);
+
+    canvas.set(0, 0, WIRE);
+    canvas.set(1, 0, WIRE);
+    canvas.commit();
+    canvas.line((0, 1), (3, 1), WIRE);
+    assert_eq!(canvas.undo_len(), 2);
+
+    // Undoing commits the open stroke first.
+    assert_eq!(canvas.undo(), Some(PixelRect::new(0, 1, 4, 1)));
+    assert_eq!(rows(&canvas), ["##..", "...."]);
+    assert_eq!(canvas.undo(), Some(PixelRect::new(0, 0, 2, 1)));
+    assert_eq!(rows(&canvas), ["....", "...."]);
+    assert_eq!(canvas.undo(), None);
+
+    assert_eq!(canvas.redo(), Some(PixelRect::new(0, 0, 2, 1)));
+    assert_eq!(rows(&canvas), ["##..", "...."]);
+    assert_eq!(canvas.redo_len(), 1);
+
+    // Drawing again drops what could be redone.
+    canvas.set(3, 0, WIRE);
+    canvas.commit();
+    assert_eq!(canvas.redo_len(), 0);
+    assert_eq!(canvas.redo(), None);
+    assert_eq!(rows(&canvas), ["##.#", "...."]);
+}
+
+#[test]
+fn reverted_strokes_leave_no_trace() {
+    let mut canvas = blank(4, 2);
+
+    // A line being dragged is drawn again every frame.
+    canvas.line((0, 0), (3, 0), WIRE);
+    assert_eq!(canvas.revert(), PixelRect::new(0, 0, 4, 1));
+    canvas.line((0, 0), (0, 1), WIRE);
+    canvas.commit();
+    assert_eq!(rows(&canvas), ["#...", "#..."]);
+
+    // Painting a pixel back to its colour is not an edit.
+    canvas.set(2, 1, WIRE);
+    canvas.set(2, 1, EMPTY);
+    canvas.commit();
+    assert_eq!(canvas.undo_len(), 1);
+
+    canvas.undo();
+    assert_eq!(rows(&canvas), ["....", "...."]);
+}
+
+#[test]
+fn saved_canvas_loads_back() {
+    let mut canvas = blank(3, 2);
+    canvas.line((0, 0), (2, 1), WIRE);
+    canvas.set(1, 1, [10, 20, 30, 40]);
+
+    let path = std::env::temp_dir().join(format!("bls-canvas-{}.png", std::process::id()));
+    canvas.save(&path).unwrap();
+    let loaded = load_png_file(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    let loaded = loaded.unwrap();
+    assert_eq!((loaded.width, loaded.height), (3, 2));
+    assert_eq!(loaded.data, canvas.pixels().data);
+}
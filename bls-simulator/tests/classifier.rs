@@ -0,0 +1,153 @@
+//! Telling wires, pins and annotations apart by pixel colour.
+
+use bls_simulator::*;
+
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+const BLACK: [u8; 4] = [0, 0, 0, 255];
+const GRAY: [u8; 4] = [200, 200, 200, 255];
+const RED: [u8; 4] = [255, 0, 0, 255];
+const GREEN: [u8; 4] = [0, 255, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+const CLEAR_WHITE: [u8; 4] = [255, 255, 255, 0];
+
+fn extract(width: u32, pixels: &[[u8; 4]], classifier: Classifier) -> Simulator {
+    let data: Vec<u8> = pixels.iter().flatten().copied().collect();
+    let height = pixels.len() as u32 / width;
+
+    Simulator::from_pixels_with_classifier(width, height, 4, &data, classifier)
+}
+
+#[test]
+fn default_threshold_ignores_alpha() {
+    let classifier = Classifier::default();
+
+    for (pixel, is_wire) in [
+        (WHITE, true),
+        (BLACK, false),
+        (GRAY, false),
+        (RED, true),
+        (CLEAR_WHITE, true),
+        ([224, 0, 0, 0], true),
+        ([223, 223, 223, 255], false),
+    ] {
+        assert_eq!(
+            classifier.classify(&pixel).is_conductive(),
+            is_wire,
+            "{:?}",
+            pixel
+        );
+    }
+}
+
+#[test]
+fn threshold() {
+    let classifier = Classifier::default().with_threshold(150);
+
+    assert_eq!(classifier.classify(&GRAY), PixelClass::Wire);
+    assert_eq!(
+        classifier.classify(&[150, 150, 150, 255]),
+        PixelClass::Empty
+    );
+}
+
+#[test]
+fn gray_pixels() {
+    let classifier = Classifier::default();
+
+    assert_eq!(classifier.classify(&[255, 255]), PixelClass::Wire);
+    assert_eq!(classifier.classify(&[10, 255]), PixelClass::Empty);
+}
+
+#[test]
+fn transparent_pixels_are_empty_when_alpha_is_considered() {
+    let classifier = Classifier::default().with_alpha_min(Some(128));
+
+    assert_eq!(classifier.classify(&CLEAR_WHITE), PixelClass::Empty);
+    assert_eq!(classifier.classify(&WHITE), PixelClass::Wire);
+    assert_eq!(
+        Classifier::default().classify(&CLEAR_WHITE),
+        PixelClass::Wire
+    );
+}
+
+#[test]
+fn palette_overrides_threshold() {
+    let classifier = Classifier::default()
+        .with_color([255, 0, 0], PixelClass::Comment)
+        .with_color([0, 0, 255], PixelClass::Wire);
+
+    assert_eq!(classifier.classify(&RED), PixelClass::Comment);
+    assert_eq!(classifier.classify(&BLUE), PixelClass::Wire);
+    assert_eq!(classifier.classify(&WHITE), PixelClass::Wire);
+    assert_eq!(classifier.classify(&GREEN), PixelClass::Wire);
+
+    let classifier = classifier.with_palette_only(true);
+    assert_eq!(classifier.classify(&WHITE), PixelClass::Empty);
+    assert_eq!(classifier.classify(&BLUE), PixelClass::Wire);
+}
+
+#[test]
+fn last_color_wins() {
+    let classifier = Classifier::default()
+        .with_color([255, 0, 0], PixelClass::Comment)
+        .with_color([255, 0, 0], PixelClass::Probe);
+
+    assert_eq!(classifier.palette.len(), 1);
+    assert_eq!(classifier.classify(&RED), PixelClass::Probe);
+}
+
+#[test]
+fn comments_do_not_conduct() {
+    // A red comment in the middle of a white line splits it in two.
+    let pixels = [WHITE, WHITE, RED, WHITE, WHITE];
+    let classifier = Classifier::default().with_color([255, 0, 0], PixelClass::Comment);
+
+    assert_eq!(extract(5, &pixels, Classifier::default()).wires.len(), 1);
+
+    let simulator = extract(5, &pixels, classifier);
+    assert_eq!(simulator.wires.len(), 2);
+    assert_eq!(simulator.wire_id_at(2, 0), None);
+    assert!(simulator.markers.is_empty());
+}
+
+#[test]
+fn pins_and_probes_conduct_and_mark_their_wire() {
+    #[rustfmt::skip]
+    let pixels = [
+        GREEN, WHITE, WHITE, BLACK, WHITE, BLUE,
+        BLACK, BLACK, BLACK, BLACK, BLACK, BLACK,
+        WHITE, RED,   WHITE, RED,   BLACK, BLACK,
+    ];
+    let classifier = Classifier::default()
+        .with_color([0, 255, 0], PixelClass::InputPin)
+        .with_color([0, 0, 255], PixelClass::OutputPin)
+        .with_color([255, 0, 0], PixelClass::Probe);
+    let simulator = extract(6, &pixels, classifier);
+
+    assert_eq!(simulator.wires.len(), 3);
+
+    let wire_at = |x, y| simulator.wire_id_at(x, y).unwrap();
+    assert_eq!(
+        simulator.markers,
+        vec![
+            Marker {
+                class: PixelClass::InputPin,
+                wire_id: wire_at(1, 0),
+                x: 0,
+                y: 0,
+            },
+            Marker {
+                class: PixelClass::OutputPin,
+                wire_id: wire_at(4, 0),
+                x: 5,
+                y: 0,
+            },
+            Marker {
+                class: PixelClass::Probe,
+                wire_id: wire_at(0, 2),
+                x: 1,
+                y: 2,
+            },
+        ]
+    );
+}
@@ -0,0 +1,134 @@
+//! The `bls` command line tool run on the golden circuits.
+
+mod common;
+
+use std::process::Command;
+
+use common::circuit_path;
+
+/// Standard output of `bls` run on the golden circuit `name` with `args`.
+fn bls(name: &str, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_bls"))
+        .arg(circuit_path(&format!("{}.png", name)))
+        .args(args)
+        .output()
+        .unwrap();
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Rows of `--trace` for steps `0..=end_step`, read back from a Value Change Dump.
+fn vcd_trace(vcd: &str, end_step: u64) -> Vec<String> {
+    let identifiers: Vec<&str> = vcd
+        .lines()
+        .filter(|line| line.starts_with("$var"))
+        .map(|line| line.split(' ').nth(3).unwrap())
+        .collect();
+    let body = vcd.split("$enddefinitions $end\n").nth(1).unwrap();
+
+    let mut states = vec!['x'; identifiers.len()];
+    let mut rows = Vec::new();
+    let mut row = |step: u64, states: &[char]| {
+        let states: Vec<String> = states.iter().map(char::to_string).collect();
+        rows.push(format!("{},{}", step, states.join(",")));
+    };
+
+    let mut time = 0;
+    for line in body.lines() {
+        if let Some(step) = line.strip_prefix('#') {
+            let step: u64 = step.parse().unwrap();
+            for earlier in time..step {
+                row(earlier, &states);
+            }
+            time = step;
+        } else if let Some(identifier) = line.get(1..).filter(|_| !line.starts_with('$')) {
+            let probe_id = identifiers.iter().position(|&id| id == identifier).unwrap();
+            states[probe_id] = line.chars().next().unwrap();
+        }
+    }
+    for last in time..=end_step {
+        row(last, &states);
+    }
+
+    rows
+}
+
+#[test]
+fn trace_and_vcd_agree() {
+    let path = std::env::temp_dir().join(format!("bls-cli-{}.vcd", std::process::id()));
+    let trace = bls(
+        "inverter_chain",
+        &[
+            "-n",
+            "6",
+            "--timing",
+            "unit",
+            "--engine",
+            "sweep",
+            "--probe",
+            "in=2,1",
+            "--probe",
+            "out=14,11",
+            "--poke",
+            "2:2,1=1",
+            "--trace",
+            "--vcd",
+            path.to_str().unwrap(),
+        ],
+    );
+    let vcd = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let mut lines = trace.lines();
+    assert_eq!(lines.next(), Some("step,in,out"));
+    assert_eq!(lines.collect::<Vec<_>>(), vcd_trace(&vcd, 6));
+}
+
+#[test]
+fn final_states_are_the_last_trace_row() {
+    let args = [
+        "-n",
+        "6",
+        "--timing",
+        "unit",
+        "--probe",
+        "in=2,1",
+        "--probe",
+        "out=14,11",
+        "--poke",
+        "2:2,1=1",
+    ];
+    let states = bls("inverter_chain", &args);
+    let trace = bls("inverter_chain", &[&args[..], &["--trace"]].concat());
+
+    assert_eq!(states, "in=1\nout=0\n");
+    assert!(trace.ends_with("\n6,1,0\n"));
+}
+
+#[test]
+fn settling_is_traced_on_every_step() {
+    let trace = bls(
+        "inverter_chain",
+        &[
+            "-n",
+            "2",
+            "--timing",
+            "unit",
+            "--probe",
+            "out=14,11",
+            "--poke",
+            "1:2,1=1",
+            "--trace",
+            "--settle",
+            "20",
+        ],
+    );
+
+    // Stable after 3 more steps, and one more step to confirm it.
+    let steps: Vec<&str> = trace
+        .lines()
+        .skip(1)
+        .map(|row| row.split(',').next().unwrap())
+        .collect();
+    assert_eq!(steps, ["0", "1", "2", "3", "4", "5", "6"]);
+}
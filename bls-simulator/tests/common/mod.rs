@@ -0,0 +1,124 @@
+//! Helpers shared by the integration tests.
+
+// Every test crate uses a different part of this module.
+#![allow(dead_code)]
+
+use bls_simulator::*;
+
+/// Three gates in a row, from the wire at (2, 1) to the wire at (14, 11).
+pub const INVERTER_CHAIN: [&str; 16] = [
+    "................",
+    "..#.............",
+    "..###...........",
+    "..##.##.........",
+    "...##.#.........",
+    "......#.........",
+    "......###.......",
+    "......##.##.....",
+    ".......##.#.....",
+    "..........#.....",
+    "..........###...",
+    "..........##.##.",
+    "...........##...",
+    "................",
+    "................",
+    "................",
+];
+
+/// Three gates in a loop, so it never settles.
+pub const RING_OSCILLATOR: [&str; 14] = [
+    "..............",
+    "..............",
+    "...##.........",
+    "..##.##.......",
+    "..###.#.......",
+    "..#...#.......",
+    "..#...###.....",
+    "..#...##.##...",
+    "..#....##.#...",
+    "..#.......#...",
+    "..#.....###...",
+    "..######.##...",
+    "........##....",
+    "..............",
+];
+
+/// Path of a file in `tests/circuits`.
+pub fn circuit_path(file_name: &str) -> String {
+    format!(
+        "{}/tests/circuits/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        file_name
+    )
+}
+
+/// Golden circuit `name`, with a fixed seed and unit delays.
+pub fn load(name: &str) -> Simulator {
+    Simulator::from_png_file(circuit_path(&format!("{}.png", name)))
+        .unwrap()
+        .with_seed(0)
+        .with_timing(TimingModel::Unit)
+}
+
+/// Golden circuit `name` with the pins of its pin file, if it has one.
+pub fn load_with_pins(name: &str) -> Simulator {
+    let mut simulator = load(name);
+    simulator
+        .load_pin_file_for(circuit_path(&format!("{}.png", name)))
+        .unwrap();
+
+    simulator
+}
+
+/// Pixels of rows of `#` (wire) and `.` (empty).
+pub type Grid = Vec<Vec<bool>>;
+
+/// Parse rows of `#` (wire) and `.` (empty).
+pub fn grid(rows: &[&str]) -> Grid {
+    rows.iter()
+        .map(|row| row.chars().map(|c| c == '#').collect())
+        .collect()
+}
+
+/// Opaque RGBA data of rows of pixels, `color` giving the colour of every pixel.
+pub fn painted<T>(
+    rows: impl IntoIterator<Item = impl IntoIterator<Item = T>>,
+    color: impl Fn(T) -> [u8; 3],
+) -> Vec<u8> {
+    rows.into_iter()
+        .flatten()
+        .flat_map(|pixel| {
+            let [r, g, b] = color(pixel);
+            [r, g, b, 255]
+        })
+        .collect()
+}
+
+/// RGBA data of `grid`, white wires on black.
+pub fn pixels(grid: &Grid) -> Vec<u8> {
+    painted(grid, |&is_wire| if is_wire { [255; 3] } else { [0; 3] })
+}
+
+/// Circuit drawn as `grid`.
+pub fn extract_grid(grid: &Grid) -> Simulator {
+    let width = grid.first().map_or(0, Vec::len);
+
+    Simulator::from_pixels(width as u32, grid.len() as u32, 4, &pixels(grid))
+}
+
+/// Circuit drawn as rows of `#` (wire) and `.` (empty).
+pub fn extract(rows: &[&str]) -> Simulator {
+    extract_grid(&grid(rows))
+}
+
+/// State of every wire and gate.
+pub fn states(simulator: &Simulator) -> (Vec<bool>, Vec<(bool, f32)>) {
+    (
+        simulator.wires.iter().map(|wire| wire.state).collect(),
+        simulator
+            .gates
+            .iter()
+            .map(|gate| (gate.state, gate.slow_state))
+            .collect(),
+    )
+}
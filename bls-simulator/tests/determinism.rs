@@ -0,0 +1,44 @@
+//! Runs with the same seed under random gate delays are reproducible.
+
+mod common;
+
+use bls_simulator::*;
+use common::{extract, states, RING_OSCILLATOR};
+
+type Trace = Vec<(Vec<bool>, Vec<(bool, f32)>)>;
+
+/// States after every step of the ring oscillator.
+fn trace(seed: u64, engine: Engine) -> Trace {
+    let mut simulator = extract(&RING_OSCILLATOR)
+        .with_seed(seed)
+        .with_engine(engine);
+    assert_eq!(simulator.gates.len(), 3);
+    let mut trace = vec![states(&simulator)];
+
+    for _ in 0..80 {
+        simulator.simulate_one_step();
+        trace.push(states(&simulator));
+    }
+
+    trace
+}
+
+#[test]
+fn same_seed_gives_the_same_trace() {
+    for engine in [Engine::Sweep, Engine::EventDriven] {
+        let first = trace(42, engine);
+        let second = trace(42, engine);
+
+        for (step, (first, second)) in first.iter().zip(&second).enumerate() {
+            assert_eq!(first, second, "{:?} differs at step {}", engine, step);
+        }
+    }
+}
+
+#[test]
+fn other_seeds_give_other_delays() {
+    assert_ne!(
+        trace(42, Engine::EventDriven),
+        trace(43, Engine::EventDriven)
+    );
+}
@@ -0,0 +1,104 @@
+//! Extraction diagnostics located on small circuits made to trigger each kind.
+
+mod common;
+
+use bls_simulator::*;
+use common::extract;
+
+/// Kind and position of every diagnostic.
+fn located(simulator: &Simulator) -> Vec<(DiagnosticKind, u32, u32)> {
+    simulator
+        .diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.kind, diagnostic.x, diagnostic.y))
+        .collect()
+}
+
+#[test]
+fn unrecognized_pattern() {
+    // Only the top left corner is filled, joining the top and left arms.
+    let simulator = extract(&[
+        ".......", "..##...", "..#.#..", "...#...", ".......", ".......", ".......",
+    ]);
+
+    assert!(simulator.gates.is_empty());
+    assert_eq!(
+        located(&simulator),
+        [
+            (DiagnosticKind::UnrecognizedPattern, 3, 2),
+            (DiagnosticKind::FloatingWire, 2, 1),
+            (DiagnosticKind::FloatingWire, 4, 2),
+            (DiagnosticKind::FloatingWire, 3, 3),
+        ]
+    );
+    assert_eq!(
+        DiagnosticKind::UnrecognizedPattern.severity(),
+        Severity::Error
+    );
+    assert_eq!(simulator.diagnostics[0].wire_id, None);
+    assert_eq!(
+        simulator.diagnostics[0].to_string(),
+        "error: (3, 2): neighbourhood is neither a crossing nor a gate"
+    );
+}
+
+#[test]
+fn floating_wire() {
+    let simulator = extract(&["......", "..###.", "....#.", "......", "......", "......"]);
+
+    assert_eq!(located(&simulator), [(DiagnosticKind::FloatingWire, 2, 1)]);
+    assert_eq!(DiagnosticKind::FloatingWire.severity(), Severity::Warning);
+    assert_eq!(simulator.diagnostics[0].wire_id, simulator.wire_id_at(2, 1));
+    assert_eq!(
+        simulator.diagnostics[0].to_string(),
+        format!(
+            "warning: (2, 1): wire {} has no driver and no load",
+            simulator.wire_id_at(2, 1).unwrap()
+        )
+    );
+}
+
+#[test]
+fn self_loop_gate() {
+    // The output of the gate pointing up runs around back into its input.
+    let simulator = extract(&[
+        "..#####", "..#...#", ".#.#..#", ".###..#", "..#####", ".......", ".......",
+    ]);
+
+    assert_eq!(simulator.gates.len(), 1);
+    assert_eq!(located(&simulator), [(DiagnosticKind::SelfLoopGate, 2, 2)]);
+    assert_eq!(DiagnosticKind::SelfLoopGate.severity(), Severity::Warning);
+    assert_eq!(simulator.diagnostics[0].gate_id, Some(0));
+    assert_eq!(simulator.diagnostics[0].wire_id, simulator.wire_id_at(2, 0));
+}
+
+#[test]
+fn shorted_outputs() {
+    // Two gates pointing up drive the wire on top.
+    let simulator = extract(&[
+        ".........",
+        "..#####..",
+        "..#...#..",
+        ".#.#.#.#.",
+        ".###.###.",
+        ".........",
+        ".........",
+        ".........",
+        ".........",
+    ]);
+
+    assert_eq!(simulator.gates.len(), 2);
+    assert_eq!(
+        located(&simulator),
+        [(DiagnosticKind::ShortedOutputs, 2, 1)]
+    );
+    assert_eq!(DiagnosticKind::ShortedOutputs.severity(), Severity::Info);
+    assert_eq!(simulator.diagnostics[0].wire_id, simulator.wire_id_at(2, 1));
+    assert_eq!(
+        diagnostics_report(&simulator.diagnostics),
+        format!(
+            "info: (2, 1): wire {} is driven by gates 0, 1\n0 errors, 0 warnings, 1 infos\n",
+            simulator.wire_id_at(2, 1).unwrap()
+        )
+    );
+}
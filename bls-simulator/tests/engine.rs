@@ -0,0 +1,82 @@
+//! The event driven engine computes the same steps as the sweep under unit delay, and the
+//! sweep still computes the steps it did before the event driven engine was added.
+
+mod common;
+
+use bls_simulator::*;
+use common::{states, INVERTER_CHAIN, RING_OSCILLATOR};
+
+/// Wires poked at a step, as (step, x, y, state).
+type Pokes = &'static [(u32, u32, u32, bool)];
+
+const CIRCUITS: [(&str, &[&str], Pokes); 2] = [
+    (
+        "inverter chain",
+        &INVERTER_CHAIN,
+        &[(0, 2, 1, true), (10, 2, 1, false), (11, 2, 1, true)],
+    ),
+    ("ring oscillator", &RING_OSCILLATOR, &[]),
+];
+
+fn extract(rows: &[&str], engine: Engine) -> Simulator {
+    common::extract(rows)
+        .with_seed(0)
+        .with_timing(TimingModel::Unit)
+        .with_engine(engine)
+}
+
+#[test]
+fn sweep_and_event_driven_agree_under_unit_delay() {
+    for (name, rows, pokes) in CIRCUITS {
+        let mut sweep = extract(rows, Engine::Sweep);
+        let mut event_driven = extract(rows, Engine::EventDriven);
+
+        for step in 0..60 {
+            for &(_, x, y, state) in pokes.iter().filter(|poke| poke.0 == step) {
+                assert!(sweep.set(x, y, state));
+                assert!(event_driven.set(x, y, state));
+            }
+
+            sweep.simulate_one_step();
+            event_driven.simulate_one_step();
+            assert_eq!(
+                states(&sweep),
+                states(&event_driven),
+                "{} differs at step {}",
+                name,
+                step
+            );
+        }
+    }
+}
+
+/// Ramp of every gate of the ring oscillator after each of its first steps with seed 7
+/// and the default random delays, as simulated before the event driven engine was added.
+const RING_OSCILLATOR_RAMPS: [[f32; 3]; 12] = [
+    [0.7843122, 0.54820335, 0.8193971],
+    [1.0, 1.0, 1.0],
+    [0.4217742, 0.057150006, 0.43282247],
+    [0.0, 0.0, 0.0],
+    [0.97248805, 0.928365, 0.8105061],
+    [1.0, 1.0, 1.0],
+    [0.04681146, 0.4643287, 0.07657361],
+    [0.0, 0.0, 0.0],
+    [0.6176944, 0.7393737, 0.69244885],
+    [1.0, 1.0, 1.0],
+    [0.39150947, 0.4951774, 0.19767153],
+    [0.0, 0.0, 0.0],
+];
+
+#[test]
+fn sweep_reproduces_random_delays_of_earlier_runs() {
+    let mut simulator = common::extract(&RING_OSCILLATOR)
+        .with_seed(7)
+        .with_engine(Engine::Sweep);
+
+    for (step, ramps) in RING_OSCILLATOR_RAMPS.iter().enumerate() {
+        simulator.simulate_one_step();
+
+        let slow_states: Vec<f32> = simulator.gates.iter().map(|gate| gate.slow_state).collect();
+        assert_eq!(slow_states, ramps, "differs at step {}", step);
+    }
+}
@@ -0,0 +1,218 @@
+//! Extraction of arbitrary images compared against a brute-force flood fill.
+
+mod common;
+
+use std::collections::{HashMap, HashSet};
+
+use bls_simulator::*;
+use common::{extract_grid, grid, Grid};
+use proptest::prelude::*;
+
+struct Reference {
+    /// Component of every pixel, `None` for empty pixels.
+    components: Vec<Vec<Option<usize>>>,
+    component_count: usize,
+    /// `(x, y, direction, input component, output component)`
+    gates: HashSet<(u32, u32, Direction, usize, usize)>,
+}
+
+const DIRECTIONS: [(i64, i64); 4] = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+
+fn reference_extract(grid: &Grid, width: usize) -> Reference {
+    let height = grid.len();
+    let is_wire = |x: i64, y: i64| {
+        x >= 0
+            && y >= 0
+            && (x as usize) < width
+            && (y as usize) < height
+            && grid[y as usize][x as usize]
+    };
+    let arms = |x: i64, y: i64| {
+        !is_wire(x, y) && DIRECTIONS.iter().all(|&(dx, dy)| is_wire(x + dx, y + dy))
+    };
+    let corners = |x: i64, y: i64| {
+        (
+            is_wire(x - 1, y + 1),
+            is_wire(x + 1, y + 1),
+            is_wire(x - 1, y - 1),
+            is_wire(x + 1, y - 1),
+        )
+    };
+    let is_crossing = |x: i64, y: i64| arms(x, y) && corners(x, y) == (false, false, false, false);
+
+    let mut components = vec![vec![None; width]; height];
+    let mut component_count = 0;
+
+    for start_y in 0..height {
+        for start_x in 0..width {
+            if !grid[start_y][start_x] || components[start_y][start_x].is_some() {
+                continue;
+            }
+
+            let mut stack = vec![(start_x as i64, start_y as i64)];
+            components[start_y][start_x] = Some(component_count);
+
+            while let Some((x, y)) = stack.pop() {
+                for &(dx, dy) in &DIRECTIONS {
+                    let (mut nx, mut ny) = (x + dx, y + dy);
+                    if !is_wire(nx, ny) {
+                        if !is_crossing(nx, ny) {
+                            continue;
+                        }
+                        // jump over the crossing
+                        nx += dx;
+                        ny += dy;
+                    }
+
+                    let component = &mut components[ny as usize][nx as usize];
+                    if component.is_none() {
+                        *component = Some(component_count);
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+
+            component_count += 1;
+        }
+    }
+
+    let mut gates = HashSet::new();
+    let component_at = |x: i64, y: i64| components[y as usize][x as usize].unwrap();
+
+    for y in 0..height as i64 {
+        for x in 0..width as i64 {
+            if !arms(x, y) {
+                continue;
+            }
+
+            let (input, output, direction) = match corners(x, y) {
+                (true, true, false, false) => ((x, y + 1), (x, y - 1), Direction::Up),
+                (false, false, true, true) => ((x, y - 1), (x, y + 1), Direction::Down),
+                (false, true, false, true) => ((x + 1, y), (x - 1, y), Direction::Left),
+                (true, false, true, false) => ((x - 1, y), (x + 1, y), Direction::Right),
+                _ => continue,
+            };
+
+            gates.insert((
+                x as u32,
+                y as u32,
+                direction,
+                component_at(input.0, input.1),
+                component_at(output.0, output.1),
+            ));
+        }
+    }
+
+    Reference {
+        components,
+        component_count,
+        gates,
+    }
+}
+
+fn assert_matches_reference(grid: &Grid, width: usize) {
+    let simulator = extract_grid(grid);
+    let reference = reference_extract(grid, width);
+
+    assert_eq!(simulator.wires.len(), reference.component_count);
+
+    // The wire ids must partition the pixels exactly like the components do.
+    let mut wire_to_component: HashMap<i32, usize> = HashMap::new();
+    for (y, row) in reference.components.iter().enumerate() {
+        for (x, &component) in row.iter().enumerate() {
+            let wire_id = simulator.wire_map[y][x];
+
+            match component {
+                None => assert_eq!(wire_id, -1, "({}, {}) is not a wire", x, y),
+                Some(component) => {
+                    assert_ne!(wire_id, -1, "({}, {}) is a wire", x, y);
+                    let mapped = *wire_to_component.entry(wire_id).or_insert(component);
+                    assert_eq!(mapped, component, "({}, {}) is on the wrong wire", x, y);
+                }
+            }
+        }
+    }
+    assert_eq!(wire_to_component.len(), reference.component_count);
+
+    let gates: HashSet<_> = simulator
+        .gates
+        .iter()
+        .map(|gate| {
+            (
+                gate.x,
+                gate.y,
+                gate.direction,
+                wire_to_component[&gate.wire_in_id],
+                wire_to_component[&gate.wire_out_id],
+            )
+        })
+        .collect();
+    assert_eq!(gates.len(), simulator.gates.len());
+    assert_eq!(gates, reference.gates);
+}
+
+#[test]
+fn wide_image_with_gate_near_right_edge() {
+    let grid = grid(&[
+        "..............##.",
+        "............###.#",
+        "..............##.",
+    ]);
+    let simulator = extract_grid(&grid);
+
+    assert_eq!(simulator.gates.len(), 1);
+    assert_eq!(simulator.gates[0].x, 15);
+    assert_eq!(simulator.gates[0].direction, Direction::Right);
+    assert_matches_reference(&grid, 17);
+}
+
+#[test]
+fn tall_image() {
+    let grid = grid(&[".#.", "#.#", ".#.", "...", "###", "#.#", ".#."]);
+
+    assert_matches_reference(&grid, 3);
+}
+
+#[test]
+fn wires_on_adjacent_rows_do_not_fuse_across_the_row_boundary() {
+    let grid = grid(&["..#", "#.."]);
+    let simulator = extract_grid(&grid);
+
+    assert_eq!(simulator.wires.len(), 2);
+    assert_ne!(simulator.wire_map[0][2], simulator.wire_map[1][0]);
+}
+
+#[test]
+fn one_pixel_wide_images() {
+    let column = grid(&["#", "#", ".", "#"]);
+    assert_eq!(extract_grid(&column).wires.len(), 2);
+    assert_matches_reference(&column, 1);
+
+    let row = grid(&["##.#"]);
+    assert_eq!(extract_grid(&row).wires.len(), 2);
+    assert_matches_reference(&row, 4);
+}
+
+#[test]
+fn empty_images() {
+    assert!(extract_grid(&Vec::new()).wires.is_empty());
+    assert!(extract_grid(&grid(&["...", "..."])).wires.is_empty());
+}
+
+fn arbitrary_grid() -> impl Strategy<Value = (Grid, usize)> {
+    (1..=12usize, 1..=12usize).prop_flat_map(|(width, height)| {
+        (
+            prop::collection::vec(prop::collection::vec(any::<bool>(), width), height),
+            Just(width),
+        )
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn extraction_matches_flood_fill((grid, width) in arbitrary_grid()) {
+        assert_matches_reference(&grid, width);
+    }
+}
@@ -0,0 +1,206 @@
+//! Golden circuit images with known structure and behaviour.
+//!
+//! The coordinates below are a pixel on each net of the images in `tests/circuits`.
+
+mod common;
+
+use bls_simulator::*;
+use common::load;
+
+const STEPS: u32 = 50;
+
+fn poke(simulator: &mut Simulator, (x, y): (u32, u32), state: bool) {
+    assert!(simulator.set(x, y, state), "no wire at ({}, {})", x, y);
+}
+
+fn probe(simulator: &Simulator, (x, y): (u32, u32)) -> bool {
+    simulator
+        .get(x, y)
+        .unwrap_or_else(|| panic!("no wire at ({}, {})", x, y))
+}
+
+mod inverter_chain {
+    use super::*;
+
+    const IN: (u32, u32) = (2, 1);
+    const OUT: (u32, u32) = (14, 11);
+
+    #[test]
+    fn extraction() {
+        let simulator = load("inverter_chain");
+
+        assert_eq!(simulator.wires.len(), 4);
+        assert_eq!(simulator.gates.len(), 3);
+        assert!(simulator.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn steady_state() {
+        let mut simulator = load("inverter_chain");
+
+        for input in [false, true, false] {
+            poke(&mut simulator, IN, input);
+            simulator.simulate(STEPS);
+            assert_eq!(probe(&simulator, OUT), !input);
+        }
+    }
+}
+
+mod crossing {
+    use super::*;
+
+    const A: (u32, u32) = (2, 1);
+    const B: (u32, u32) = (6, 1);
+    const NOT_A: (u32, u32) = (10, 3);
+    const NOT_B: (u32, u32) = (14, 7);
+
+    #[test]
+    fn extraction() {
+        let simulator = load("crossing");
+
+        assert_eq!(simulator.wires.len(), 4);
+        assert_eq!(simulator.gates.len(), 2);
+        // Both sides of each wire through the crossing at (6, 3) are the same net.
+        assert_eq!(simulator.wire_id_at(5, 3), simulator.wire_id_at(7, 3));
+        assert_eq!(simulator.wire_id_at(6, 2), simulator.wire_id_at(6, 4));
+        assert_ne!(simulator.wire_id_at(5, 3), simulator.wire_id_at(6, 2));
+    }
+
+    #[test]
+    fn steady_state() {
+        let mut simulator = load("crossing");
+
+        for (a, b) in [(false, false), (false, true), (true, false), (true, true)] {
+            poke(&mut simulator, A, a);
+            poke(&mut simulator, B, b);
+            simulator.simulate(STEPS);
+            assert_eq!(probe(&simulator, NOT_A), !a, "a = {}, b = {}", a, b);
+            assert_eq!(probe(&simulator, NOT_B), !b, "a = {}, b = {}", a, b);
+        }
+    }
+}
+
+mod sr_latch {
+    use super::*;
+
+    const S: (u32, u32) = (2, 1);
+    const R: (u32, u32) = (6, 1);
+    const Q_BAR: (u32, u32) = (18, 15);
+    const Q: (u32, u32) = (22, 11);
+
+    #[test]
+    fn extraction() {
+        let simulator = load("sr_latch");
+
+        assert_eq!(simulator.wires.len(), 6);
+        assert_eq!(simulator.gates.len(), 6);
+    }
+
+    #[test]
+    fn set_reset_and_hold() {
+        let mut simulator = load("sr_latch");
+
+        let pulse = |simulator: &mut Simulator, pin| {
+            poke(simulator, pin, true);
+            simulator.simulate(STEPS);
+            poke(simulator, pin, false);
+            simulator.simulate(STEPS);
+        };
+
+        pulse(&mut simulator, S);
+        assert!(probe(&simulator, Q));
+        assert!(!probe(&simulator, Q_BAR));
+
+        pulse(&mut simulator, R);
+        assert!(!probe(&simulator, Q));
+        assert!(probe(&simulator, Q_BAR));
+
+        pulse(&mut simulator, S);
+        assert!(probe(&simulator, Q));
+        assert!(!probe(&simulator, Q_BAR));
+    }
+}
+
+mod ring_oscillator {
+    use super::*;
+
+    const A: (u32, u32) = (2, 3);
+
+    #[test]
+    fn extraction() {
+        let simulator = load("ring_oscillator");
+
+        assert_eq!(simulator.wires.len(), 3);
+        assert_eq!(simulator.gates.len(), 3);
+    }
+
+    #[test]
+    fn oscillates_every_step_from_the_symmetric_start() {
+        // Every gate starts low and sees a low input, so under unit delay all three
+        // switch together on every step instead of passing an edge around the ring.
+        let mut simulator = load("ring_oscillator");
+        simulator.simulate(STEPS);
+
+        let trace: Vec<bool> = (0..24)
+            .map(|_| {
+                simulator.simulate_one_step();
+                probe(&simulator, A)
+            })
+            .collect();
+
+        for step in 0..trace.len() - 1 {
+            assert_ne!(trace[step], trace[step + 1]);
+        }
+    }
+}
+
+mod half_adder {
+    use super::*;
+
+    const A: (u32, u32) = (2, 1);
+    const B: (u32, u32) = (6, 1);
+    const CARRY: (u32, u32) = (30, 35);
+    const SUM: (u32, u32) = (34, 39);
+
+    #[test]
+    fn extraction() {
+        let simulator = load("half_adder");
+
+        assert_eq!(simulator.wires.len(), 9);
+        assert_eq!(simulator.gates.len(), 10);
+    }
+
+    #[test]
+    fn truth_table() {
+        for timing in [
+            TimingModel::Unit,
+            TimingModel::Ideal,
+            TimingModel::default(),
+        ] {
+            let mut simulator = load("half_adder").with_timing(timing);
+
+            for (a, b) in [(false, false), (false, true), (true, false), (true, true)] {
+                poke(&mut simulator, A, a);
+                poke(&mut simulator, B, b);
+                simulator.simulate(STEPS);
+
+                assert_eq!(
+                    probe(&simulator, SUM),
+                    a ^ b,
+                    "{:?} a = {}, b = {}",
+                    timing,
+                    a,
+                    b
+                );
+                assert_eq!(
+                    probe(&simulator, CARRY),
+                    a && b,
+                    "{:?} a = {}, b = {}",
+                    timing,
+                    a,
+                    b
+                );
+            }
+        }
+    }
+}
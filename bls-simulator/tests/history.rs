@@ -0,0 +1,165 @@
+//! Stepping back and forward through the history of golden circuits.
+
+mod common;
+
+use bls_simulator::*;
+use common::states;
+
+fn load(name: &str) -> Simulator {
+    common::load(name)
+        .with_seed(3)
+        .with_timing(TimingModel::default())
+}
+
+#[test]
+fn steps_back_and_forward_through_every_state() {
+    for engine in [Engine::Sweep, Engine::EventDriven] {
+        let mut simulator = load("sr_latch").with_engine(engine).with_history(100);
+        let mut seen = vec![states(&simulator)];
+
+        for step in 0..60 {
+            // Pulse set, then reset.
+            match step {
+                0 => assert!(simulator.set(2, 1, true)),
+                20 => assert!(simulator.set(2, 1, false)),
+                30 => assert!(simulator.set(6, 1, true)),
+                _ => {}
+            }
+            simulator.simulate_one_step();
+            seen.push(states(&simulator));
+        }
+
+        for step in (0..60).rev() {
+            assert!(simulator.step_back());
+            assert_eq!(simulator.step_count(), step);
+            assert!(simulator.is_rewound());
+        }
+        assert!(!simulator.step_back());
+
+        // Wires set before a step are undone with it.
+        assert_eq!(states(&simulator), seen[0], "{:?}", engine);
+
+        for (step, seen) in seen.iter().enumerate().skip(1) {
+            assert!(simulator.step_forward());
+            assert_eq!(&states(&simulator), seen, "{:?} at {}", engine, step);
+        }
+        assert!(!simulator.step_forward());
+        assert!(!simulator.is_rewound());
+    }
+}
+
+#[test]
+fn continues_as_if_never_rewound() {
+    for engine in [Engine::Sweep, Engine::EventDriven] {
+        let mut original = load("sr_latch").with_engine(engine);
+        let mut rewound = load("sr_latch").with_engine(engine).with_history(16);
+
+        for simulator in [&mut original, &mut rewound] {
+            simulator.set(2, 1, true);
+            simulator.simulate(10);
+        }
+
+        assert!(rewound.seek(2));
+        assert!(rewound.seek(10));
+
+        for _ in 0..30 {
+            original.simulate_one_step();
+            rewound.simulate_one_step();
+            assert_eq!(states(&rewound), states(&original), "{:?}", engine);
+        }
+    }
+}
+
+#[test]
+fn keeps_the_last_steps_only() {
+    let mut simulator = load("ring_oscillator");
+    assert_eq!(simulator.history_range(), None);
+    assert!(!simulator.step_back());
+
+    let mut simulator = simulator.with_history(10);
+    simulator.simulate(50);
+    assert_eq!(simulator.history_range(), Some(40..=50));
+    assert_eq!(simulator.history().unwrap().undo_len(), 10);
+
+    assert!(!simulator.seek(39));
+    assert!(!simulator.seek(51));
+    assert_eq!(simulator.step_count(), 50);
+
+    assert!(simulator.seek(40));
+    assert_eq!(simulator.step_count(), 40);
+    assert!(!simulator.step_back());
+    assert_eq!(simulator.history_range(), Some(40..=50));
+}
+
+#[test]
+fn setting_a_wire_discards_undone_steps() {
+    let mut simulator = load("sr_latch").with_history(100);
+    simulator.set(2, 1, true);
+    simulator.simulate(20);
+
+    simulator.seek(15);
+    assert_eq!(simulator.history_range(), Some(0..=20));
+
+    assert!(simulator.set(6, 1, true));
+    assert!(!simulator.is_rewound());
+    assert_eq!(simulator.history_range(), Some(0..=15));
+
+    simulator.simulate_one_step();
+    assert_eq!(simulator.history_range(), Some(0..=16));
+    assert!(!simulator.step_forward());
+}
+
+#[test]
+fn restoring_a_snapshot_starts_over() {
+    let mut simulator = load("ring_oscillator").with_history(100);
+    simulator.simulate(5);
+    let snapshot = simulator.snapshot();
+    simulator.simulate(5);
+
+    simulator.restore(&snapshot).unwrap();
+    assert_eq!(simulator.history_range(), Some(5..=5));
+
+    simulator.simulate(3);
+    assert!(simulator.seek(5));
+    assert_eq!(states(&simulator), {
+        let mut restored = load("ring_oscillator");
+        restored.restore(&snapshot).unwrap();
+        states(&restored)
+    });
+}
+
+#[test]
+fn waveform_follows_the_new_course() {
+    const S: (u32, u32) = (2, 1);
+    const R: (u32, u32) = (6, 1);
+    const Q_BAR: (u32, u32) = (18, 15);
+    const Q: (u32, u32) = (22, 11);
+
+    let vcd = |simulator: &Simulator| {
+        let mut out = Vec::new();
+        simulator.write_vcd(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+
+    let mut original = load("sr_latch");
+    let mut rewound = load("sr_latch").with_history(100);
+    for simulator in [&mut original, &mut rewound] {
+        assert!(simulator.add_probe("s", S.0, S.1));
+        assert!(simulator.add_probe("q", Q.0, Q.1));
+        simulator.set(S.0, S.1, true);
+        simulator.simulate(10);
+    }
+
+    original.add_probe("q_bar", Q_BAR.0, Q_BAR.1);
+    original.set(R.0, R.1, true);
+    original.simulate(10);
+
+    // Steps 11 to 20 are recorded, then undone and simulated again with R set.
+    rewound.simulate(10);
+    rewound.add_probe("q_bar", Q_BAR.0, Q_BAR.1);
+    assert!(rewound.seek(10));
+    rewound.set(R.0, R.1, true);
+    rewound.simulate(10);
+
+    assert_eq!(vcd(&rewound), vcd(&original));
+}
@@ -0,0 +1,181 @@
+//! Every PNG color type and bit depth is normalized to 8-bit RGBA.
+
+use bls_simulator::*;
+
+/// Encode a 2x1 PNG.
+fn encode(
+    color_type: png::ColorType,
+    bit_depth: png::BitDepth,
+    data: &[u8],
+    palette: Option<(&[u8], &[u8])>,
+) -> Vec<u8> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, 2, 1);
+    encoder.set_color(color_type);
+    encoder.set_depth(bit_depth);
+    if let Some((palette, trns)) = palette {
+        encoder.set_palette(palette.to_vec());
+        encoder.set_trns(trns.to_vec());
+    }
+
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(data).unwrap();
+    writer.finish().unwrap();
+
+    png
+}
+
+fn load(png: &[u8]) -> Vec<u8> {
+    let pixels = load_png(png).unwrap();
+
+    assert_eq!((pixels.width, pixels.height, pixels.components), (2, 1, 4));
+    pixels.data
+}
+
+#[test]
+fn rgba() {
+    let png = encode(
+        png::ColorType::Rgba,
+        png::BitDepth::Eight,
+        &[255, 0, 0, 255, 1, 2, 3, 4],
+        None,
+    );
+
+    assert_eq!(load(&png), [255, 0, 0, 255, 1, 2, 3, 4]);
+}
+
+#[test]
+fn rgb() {
+    let png = encode(
+        png::ColorType::Rgb,
+        png::BitDepth::Eight,
+        &[255, 0, 0, 1, 2, 3],
+        None,
+    );
+
+    assert_eq!(load(&png), [255, 0, 0, 255, 1, 2, 3, 255]);
+}
+
+#[test]
+fn grayscale() {
+    let png = encode(
+        png::ColorType::Grayscale,
+        png::BitDepth::Eight,
+        &[255, 7],
+        None,
+    );
+
+    assert_eq!(load(&png), [255, 255, 255, 255, 7, 7, 7, 255]);
+}
+
+#[test]
+fn grayscale_alpha() {
+    let png = encode(
+        png::ColorType::GrayscaleAlpha,
+        png::BitDepth::Eight,
+        &[255, 0, 7, 128],
+        None,
+    );
+
+    assert_eq!(load(&png), [255, 255, 255, 0, 7, 7, 7, 128]);
+}
+
+#[test]
+fn one_bit_grayscale() {
+    // Both pixels packed into the high bits of a single byte.
+    let png = encode(
+        png::ColorType::Grayscale,
+        png::BitDepth::One,
+        &[0b1000_0000],
+        None,
+    );
+
+    assert_eq!(load(&png), [255, 255, 255, 255, 0, 0, 0, 255]);
+}
+
+#[test]
+fn sixteen_bit_rgb() {
+    // Big-endian channels.
+    let png = encode(
+        png::ColorType::Rgb,
+        png::BitDepth::Sixteen,
+        &[0xff, 0xff, 0x12, 0x34, 0, 0, 0, 0, 0, 0, 0xab, 0xcd],
+        None,
+    );
+
+    assert_eq!(load(&png), [0xff, 0x12, 0, 255, 0, 0, 0xab, 255]);
+}
+
+#[test]
+fn indexed_with_transparency() {
+    let png = encode(
+        png::ColorType::Indexed,
+        png::BitDepth::Eight,
+        &[1, 0],
+        Some((&[10, 20, 30, 255, 255, 255], &[0])),
+    );
+
+    assert_eq!(load(&png), [255, 255, 255, 255, 10, 20, 30, 0]);
+}
+
+#[test]
+fn indexed_circuit_extracts_like_rgba() {
+    // A three pixel white line in a 2 bit palette image.
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, 5, 1);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Two);
+    encoder.set_palette(vec![0, 0, 0, 255, 255, 255]);
+    let mut writer = encoder.write_header().unwrap();
+    writer
+        .write_image_data(&[0b0001_0101, 0b0000_0000])
+        .unwrap();
+    writer.finish().unwrap();
+
+    let pixels = load_png(&png[..]).unwrap();
+    let simulator =
+        Simulator::from_pixels(pixels.width, pixels.height, pixels.components, &pixels.data);
+
+    assert_eq!(simulator.wires.len(), 1);
+    assert_eq!(simulator.wire_id_at(0, 0), None);
+    assert_eq!(simulator.wire_id_at(3, 0), Some(0));
+    assert_eq!(simulator.wire_id_at(4, 0), None);
+}
+
+#[test]
+fn raw_layouts() {
+    let layout = |color, bit_depth| PixelLayout { color, bit_depth };
+
+    let pixels = PixelBuffer::from_raw(2, 1, layout(ColorLayout::Gray, 8), &[255, 7]).unwrap();
+    assert_eq!(pixels.data, [255, 255, 255, 255, 7, 7, 7, 255]);
+
+    let data: Vec<u8> = [0xff00u16, 0x1234, 0x0000, 0xabcd]
+        .iter()
+        .flat_map(|channel| channel.to_ne_bytes())
+        .collect();
+    let pixels = PixelBuffer::from_raw(2, 1, layout(ColorLayout::GrayAlpha, 16), &data).unwrap();
+    assert_eq!(pixels.data, [0xff, 0xff, 0xff, 0x12, 0, 0, 0, 0xab]);
+
+    let pixels = PixelBuffer::from_raw(1, 1, PixelLayout::rgba8(), &[1, 2, 3, 4]).unwrap();
+    assert_eq!(pixels.data, [1, 2, 3, 4]);
+}
+
+#[test]
+fn raw_layout_errors() {
+    assert!(matches!(
+        PixelBuffer::from_raw(2, 2, PixelLayout::rgba8(), &[0; 15]),
+        Err(LoadError::Unsupported(_))
+    ));
+    assert!(matches!(
+        PixelBuffer::from_raw(
+            1,
+            1,
+            PixelLayout {
+                color: ColorLayout::Gray,
+                bit_depth: 4
+            },
+            &[0]
+        ),
+        Err(LoadError::Unsupported(_))
+    ));
+}
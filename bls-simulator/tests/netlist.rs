@@ -0,0 +1,89 @@
+//! JSON and Graphviz exports of the extracted netlist.
+
+mod common;
+
+use bls_simulator::*;
+use serde_json::json;
+
+/// A gate pointing right, from the wire around it on the left to a wire of two pixels.
+fn single_gate() -> Simulator {
+    common::extract(&["##...", "#.##.", "##...", ".....", "....#"])
+}
+
+#[test]
+fn json() {
+    let json: serde_json::Value = serde_json::from_str(&single_gate().netlist().to_json()).unwrap();
+
+    assert_eq!(
+        json,
+        json!({
+            "width": 5,
+            "height": 5,
+            "wires": [
+                {
+                    "id": 0,
+                    "bounds": { "min_x": 2, "min_y": 1, "max_x": 3, "max_y": 1 },
+                    "pixel_count": 2,
+                    "drivers": [0],
+                    "loads": []
+                },
+                {
+                    "id": 1,
+                    "bounds": { "min_x": 0, "min_y": 0, "max_x": 1, "max_y": 2 },
+                    "pixel_count": 5,
+                    "drivers": [],
+                    "loads": [0]
+                },
+                {
+                    "id": 2,
+                    "bounds": { "min_x": 4, "min_y": 4, "max_x": 4, "max_y": 4 },
+                    "pixel_count": 1,
+                    "drivers": [],
+                    "loads": []
+                }
+            ],
+            "gates": [
+                { "id": 0, "x": 1, "y": 1, "direction": "right", "input": 1, "output": 0 }
+            ]
+        })
+    );
+}
+
+#[test]
+fn dot() {
+    let mut out = Vec::new();
+    single_gate().netlist().write_dot(&mut out).unwrap();
+
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        r#"digraph circuit {
+    rankdir=LR;
+    w0 [shape=ellipse, label="w0\n(2, 1)-(3, 1)"];
+    w1 [shape=ellipse, label="w1\n(0, 0)-(1, 2)"];
+    w2 [shape=ellipse, label="w2\n(4, 4)-(4, 4)"];
+    g0 [shape=triangle, orientation=-90, label="g0 →\n(1, 1)"];
+    w1 -> g0;
+    g0 -> w0;
+}
+"#
+    );
+}
+
+#[test]
+fn wires_without_pixels_have_no_bounds() {
+    let mut simulator = single_gate();
+    simulator.wires.push(Wire::new());
+    let netlist = simulator.netlist();
+
+    assert_eq!(netlist.wires[3].bounds, None);
+    assert_eq!(netlist.wires[3].pixel_count, 0);
+
+    let json: serde_json::Value = serde_json::from_str(&netlist.to_json()).unwrap();
+    assert_eq!(json["wires"][3]["bounds"], serde_json::Value::Null);
+
+    let mut out = Vec::new();
+    netlist.write_dot(&mut out).unwrap();
+    assert!(String::from_utf8(out)
+        .unwrap()
+        .contains("    w3 [shape=ellipse];\n"));
+}
@@ -0,0 +1,217 @@
+//! Named pins and buses from pin files.
+
+mod common;
+
+use bls_simulator::*;
+use common::circuit_path;
+
+const STEPS: u32 = 50;
+
+fn half_adder() -> Simulator {
+    let mut simulator = common::load("half_adder");
+
+    assert!(simulator
+        .load_pin_file_for(circuit_path("half_adder.png"))
+        .unwrap());
+    simulator
+}
+
+fn resolve(text: &str) -> Result<Pins, PinError> {
+    let simulator = common::load("half_adder");
+
+    Pins::resolve(&PinFile::parse(text)?, &simulator)
+}
+
+#[test]
+fn pin_file_path_appends_toml() {
+    assert_eq!(
+        pin_file_path("circuits/adder.png"),
+        std::path::PathBuf::from("circuits/adder.png.toml")
+    );
+}
+
+#[test]
+fn missing_pin_file_is_not_an_error() {
+    let path = circuit_path("inverter_chain.png");
+    let mut simulator = Simulator::from_png_file(&path).unwrap();
+
+    assert!(!simulator.load_pin_file_for(&path).unwrap());
+    assert!(simulator.pins().is_empty());
+}
+
+#[test]
+fn pins_resolve_to_wires() {
+    let simulator = half_adder();
+    let pins = simulator.pins();
+
+    let a = pins.pin("a").unwrap();
+    assert_eq!(a.direction, PortDirection::Input);
+    assert_eq!(Some(a.wire_id), simulator.wire_id_at(2, 1));
+
+    let sum = pins.pin("sum").unwrap();
+    assert_eq!(sum.direction, PortDirection::Output);
+    assert_eq!(Some(sum.wire_id), simulator.wire_id_at(34, 39));
+
+    assert!(pins.pin("operands").is_none());
+    assert!(pins.pin("missing").is_none());
+}
+
+#[test]
+fn bus_bits_are_least_significant_first() {
+    let simulator = half_adder();
+    let pins = simulator.pins();
+
+    let operands = pins.bus("operands").unwrap();
+    assert_eq!((operands.bits[0].x, operands.bits[0].y), (2, 1));
+    assert_eq!(operands.bits[1].name, "operands[1]");
+
+    // msb_first lists carry before sum
+    let result = pins.bus("result").unwrap();
+    assert_eq!(result.direction, PortDirection::Output);
+    assert_eq!(result.bits[0].wire_id, pins.pin("sum").unwrap().wire_id);
+    assert_eq!(result.bits[1].wire_id, pins.pin("carry").unwrap().wire_id);
+}
+
+#[test]
+fn drive_and_read_by_name() {
+    let mut simulator = half_adder();
+
+    for (a, b) in [(false, false), (false, true), (true, false), (true, true)] {
+        assert!(simulator.set_pin("a", a));
+        assert!(simulator.set_pin("b", b));
+        simulator.simulate(STEPS);
+
+        assert_eq!(simulator.get_pin("sum"), Some(a ^ b));
+        assert_eq!(simulator.get_pin("carry"), Some(a && b));
+        assert_eq!(simulator.get_bus_bits("result"), Some(vec![a ^ b, a && b]));
+    }
+
+    assert!(!simulator.set_pin("missing", true));
+    assert_eq!(simulator.get_pin("missing"), None);
+}
+
+#[test]
+fn drive_bus_bits() {
+    let mut simulator = half_adder();
+
+    assert!(simulator.set_bus_bits("operands", &[true, true]));
+    simulator.simulate(STEPS);
+    assert_eq!(simulator.get_bus_bits("result"), Some(vec![false, true]));
+
+    assert!(!simulator.set_bus_bits("operands", &[true]));
+    assert!(!simulator.set_bus_bits("missing", &[true]));
+    assert_eq!(simulator.get_bus_bits("missing"), None);
+}
+
+#[test]
+fn pin_not_on_a_wire() {
+    let err = resolve("[inputs]\na = [0, 0]\n").unwrap_err();
+
+    assert!(matches!(err, PinError::NotOnWire { x: 0, y: 0, .. }));
+    assert_eq!(err.to_string(), "pin a at (0, 0) is not on a wire");
+}
+
+#[test]
+fn bus_bit_not_on_a_wire() {
+    let text =
+        "[buses.x]\ndirection = \"input\"\nbit_order = \"msb_first\"\npins = [[0, 0], [2, 1]]\n";
+
+    assert_eq!(
+        resolve(text).unwrap_err().to_string(),
+        "pin x[1] at (0, 0) is not on a wire"
+    );
+}
+
+#[test]
+fn invalid_pin_files() {
+    assert!(matches!(
+        resolve("[inputs]\na = [2, 1]\n[outputs]\na = [34, 39]\n"),
+        Err(PinError::DuplicateName(name)) if name == "a"
+    ));
+    assert!(matches!(
+        resolve("[buses.x]\ndirection = \"input\"\npins = []\n"),
+        Err(PinError::EmptyBus(name)) if name == "x"
+    ));
+    assert!(matches!(
+        resolve("[input]\na = [2, 1]\n"),
+        Err(PinError::Parse(_))
+    ));
+    assert!(matches!(
+        resolve("[buses.x]\ndirection = \"inout\"\npins = [[2, 1]]\n"),
+        Err(PinError::Parse(_))
+    ));
+}
+
+/// Three wires, starting with an input pin pixel, an output pin pixel and a plain one.
+fn marked() -> (Simulator, Vec<u8>) {
+    const INPUT: [u8; 3] = [0, 255, 0];
+    const OUTPUT: [u8; 3] = [0, 0, 255];
+
+    let rows = ["i###", "....", "....", "o###", "....", "####"];
+    let data = common::painted(rows.map(str::chars), |c| match c {
+        'i' => INPUT,
+        'o' => OUTPUT,
+        '#' => [255; 3],
+        _ => [0; 3],
+    });
+
+    let classifier = Classifier::default()
+        .with_color(INPUT, PixelClass::InputPin)
+        .with_color(OUTPUT, PixelClass::OutputPin);
+    let simulator = Simulator::from_pixels_with_classifier(4, 6, 4, &data, classifier);
+
+    (simulator, data)
+}
+
+#[test]
+fn pin_colours_are_pins() {
+    let (mut simulator, _) = marked();
+    let names = |simulator: &Simulator| -> Vec<(String, PortDirection)> {
+        simulator
+            .pins()
+            .pins
+            .iter()
+            .map(|pin| (pin.name.clone(), pin.direction))
+            .collect()
+    };
+
+    assert_eq!(
+        names(&simulator),
+        [
+            ("w0".to_string(), PortDirection::Input),
+            ("w1".to_string(), PortDirection::Output),
+        ]
+    );
+    assert!(simulator.set_pin("w0", true));
+    assert_eq!(simulator.get_pin("w1"), Some(false));
+
+    // A pin of the file going the same way names the wire instead.
+    simulator
+        .load_pins(&PinFile::parse("[inputs]\na = [3, 0]\nw1 = [3, 5]\n").unwrap())
+        .unwrap();
+    assert_eq!(
+        names(&simulator),
+        [
+            ("a".to_string(), PortDirection::Input),
+            ("w1".to_string(), PortDirection::Input),
+        ]
+    );
+}
+
+#[test]
+fn pin_colours_are_renamed_with_their_wires() {
+    let (mut simulator, mut data) = marked();
+
+    // Erase the top wire, so the output pin wire becomes the first one.
+    for pixel in data[..4 * 4].chunks_exact_mut(4) {
+        pixel[..3].copy_from_slice(&[0, 0, 0]);
+    }
+    simulator.reextract(&data, 4, PixelRect::new(0, 0, 4, 1));
+
+    let pins = &simulator.pins().pins;
+    assert_eq!(pins.len(), 1);
+    assert_eq!(pins[0].name, "w0");
+    assert_eq!(pins[0].wire_id, 0);
+    assert_eq!(pins[0].direction, PortDirection::Output);
+    assert_eq!((pins[0].x, pins[0].y), (0, 3));
+}
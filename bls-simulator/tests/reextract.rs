@@ -0,0 +1,237 @@
+//! Extracting the circuit again around edited pixels, compared against a full extraction.
+
+mod common;
+
+use std::collections::HashMap;
+
+use bls_simulator::*;
+use common::{extract_grid, grid, pixels, Grid};
+use proptest::prelude::*;
+
+/// Paint `rect` of `grid` with `fill`, row by row, and extract the changed region again.
+fn edit(simulator: &mut Simulator, grid: &mut Grid, rect: PixelRect, fill: &[bool]) -> CarryOver {
+    let mut fill = fill.iter().cycle();
+    for y in rect.y..rect.y + rect.height {
+        for x in rect.x..rect.x + rect.width {
+            grid[y as usize][x as usize] = *fill.next().unwrap();
+        }
+    }
+
+    simulator.reextract(&pixels(grid), 4, rect)
+}
+
+/// Check that `simulator` is the circuit a full extraction of `grid` gives, up to wire ids.
+fn assert_matches_full_extraction(simulator: &Simulator, grid: &Grid) {
+    let expected = extract_grid(grid);
+    assert_eq!(simulator.wires.len(), expected.wires.len());
+
+    // Both wire maps must partition the pixels the same way.
+    let mut wire_ids: HashMap<i32, i32> = HashMap::new();
+    for (row, expected_row) in simulator.wire_map.iter().zip(&expected.wire_map) {
+        for (&wire_id, &expected_id) in row.iter().zip(expected_row) {
+            assert_eq!(wire_id == -1, expected_id == -1);
+            if wire_id != -1 {
+                assert_eq!(*wire_ids.entry(expected_id).or_insert(wire_id), wire_id);
+            }
+        }
+    }
+    let mut mapped: Vec<i32> = wire_ids.values().copied().collect();
+    mapped.sort_unstable();
+    mapped.dedup();
+    assert_eq!(mapped.len(), wire_ids.len());
+
+    assert_eq!(simulator.gates.len(), expected.gates.len());
+    for (gate, expected_gate) in simulator.gates.iter().zip(&expected.gates) {
+        assert_eq!(
+            (gate.x, gate.y, gate.direction),
+            (expected_gate.x, expected_gate.y, expected_gate.direction)
+        );
+        assert_eq!(gate.wire_in_id, wire_ids[&expected_gate.wire_in_id]);
+        assert_eq!(gate.wire_out_id, wire_ids[&expected_gate.wire_out_id]);
+    }
+
+    for (&expected_id, &wire_id) in &wire_ids {
+        let wire = &simulator.wires[wire_id as usize];
+        let expected_wire = &expected.wires[expected_id as usize];
+        assert_eq!(wire.gate_in_ids, expected_wire.gate_in_ids);
+        assert_eq!(wire.gate_out_ids, expected_wire.gate_out_ids);
+    }
+
+    let located = |simulator: &Simulator| {
+        let mut diagnostics: Vec<_> = simulator
+            .diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.x, diagnostic.y, diagnostic.kind as u8))
+            .collect();
+        diagnostics.sort_unstable();
+        diagnostics
+    };
+    assert_eq!(located(simulator), located(&expected));
+    assert_eq!(simulator.image_hash(), expected.image_hash());
+}
+
+#[test]
+fn drawing_a_gate_connects_it() {
+    let mut grid = grid(&[
+        "..#......",
+        ".........",
+        ".#.#.....",
+        ".###.....",
+        ".........",
+        "#########",
+    ]);
+    let mut simulator = extract_grid(&grid);
+    assert!(simulator.gates.is_empty());
+
+    edit(
+        &mut simulator,
+        &mut grid,
+        PixelRect::new(2, 1, 1, 1),
+        &[true],
+    );
+
+    assert_eq!(simulator.gates.len(), 1);
+    assert_eq!(simulator.gates[0].direction, Direction::Up);
+    assert_matches_full_extraction(&simulator, &grid);
+}
+
+#[test]
+fn wires_away_from_the_edit_keep_their_state() {
+    let mut grid = grid(&[
+        "#######..",
+        ".........",
+        ".........",
+        "#########",
+        ".........",
+        "#########",
+    ]);
+    let mut simulator = extract_grid(&grid);
+    assert!(simulator.set(0, 0, true));
+    assert!(simulator.set(0, 3, true));
+    assert!(simulator.set(0, 5, true));
+
+    // Lengthen the top wire and cut the middle one.
+    let carry_over = edit(
+        &mut simulator,
+        &mut grid,
+        PixelRect::new(7, 0, 2, 1),
+        &[true],
+    );
+    assert_eq!(carry_over.wires, 2);
+    // The bottom wire is near enough to be looked at again, but made of the same pixels.
+    let carry_over = edit(
+        &mut simulator,
+        &mut grid,
+        PixelRect::new(4, 3, 1, 1),
+        &[false],
+    );
+    assert_eq!(carry_over.wires, 2);
+
+    assert_eq!(simulator.get(0, 0), Some(false));
+    assert_eq!(simulator.get(0, 3), Some(false));
+    assert_eq!(simulator.get(8, 3), Some(false));
+    assert_eq!(simulator.get(0, 5), Some(true));
+    assert_matches_full_extraction(&simulator, &grid);
+}
+
+#[test]
+fn pins_and_markers_follow_their_wires() {
+    const INPUT: [u8; 3] = [0, 255, 0];
+    let classifier = Classifier::default().with_color(INPUT, PixelClass::InputPin);
+
+    // Two wires, the lower one starting with an input pin pixel.
+    let mut data = pixels(&grid(&["######", "......", "######"]));
+    data[12 * 4..12 * 4 + 3].copy_from_slice(&INPUT);
+    let mut simulator = Simulator::from_pixels_with_classifier(6, 3, 4, &data, classifier.clone());
+    simulator
+        .load_pins(&PinFile::parse("[inputs]\na = [0, 2]\nb = [5, 0]\n").unwrap())
+        .unwrap();
+
+    // Erase the end of the top wire under pin b, and join the wires.
+    data[5 * 4..5 * 4 + 3].copy_from_slice(&[0, 0, 0]);
+    data[(6 + 2) * 4..(6 + 2) * 4 + 3].copy_from_slice(&[255, 255, 255]);
+    simulator.reextract(&data, 4, PixelRect::new(2, 0, 4, 2));
+
+    let expected = Simulator::from_pixels_with_classifier(6, 3, 4, &data, classifier);
+    assert_eq!(simulator.wires.len(), 1);
+    assert_eq!(simulator.markers, expected.markers);
+    assert_eq!(simulator.pins().pins.len(), 1);
+    assert_eq!(simulator.pins().pins[0].name, "a");
+    assert_eq!(simulator.pins().pins[0].wire_id, 0);
+}
+
+#[test]
+fn probes_follow_the_pixel_they_were_put_on() {
+    let mut grid = grid(&[".......", "#######", "......."]);
+    let mut simulator = extract_grid(&grid);
+    assert!(simulator.add_probe("left", 0, 1));
+    assert!(simulator.add_probe("middle", 3, 1));
+    assert!(simulator.add_probe("right", 6, 1));
+
+    // Cut the wire in two under the middle probe.
+    edit(
+        &mut simulator,
+        &mut grid,
+        PixelRect::new(3, 1, 1, 1),
+        &[false],
+    );
+
+    let probes: Vec<(&str, Option<u32>)> = simulator
+        .recorder()
+        .probes()
+        .iter()
+        .map(|probe| (probe.name.as_str(), Some(probe.wire_id)))
+        .collect();
+    assert_eq!(
+        probes,
+        [
+            ("left", simulator.wire_id_at(0, 1)),
+            ("right", simulator.wire_id_at(6, 1)),
+        ]
+    );
+    assert_ne!(simulator.wire_id_at(0, 1), simulator.wire_id_at(6, 1));
+}
+
+#[derive(Debug, Clone)]
+struct Edit {
+    rect: PixelRect,
+    fill: Vec<bool>,
+}
+
+fn arbitrary_edit(width: u32, height: u32) -> impl Strategy<Value = Edit> {
+    (0..width, 0..height)
+        .prop_flat_map(move |(x, y)| (Just(x), Just(y), 1..=width - x, 1..=height - y))
+        .prop_flat_map(|(x, y, width, height)| {
+            (
+                Just(PixelRect::new(x, y, width, height)),
+                prop::collection::vec(any::<bool>(), (width * height) as usize),
+            )
+        })
+        .prop_map(|(rect, fill)| Edit { rect, fill })
+}
+
+fn arbitrary_edits() -> impl Strategy<Value = (Grid, Vec<Edit>)> {
+    (1..=12u32, 1..=12u32).prop_flat_map(|(width, height)| {
+        (
+            prop::collection::vec(
+                prop::collection::vec(any::<bool>(), width as usize),
+                height as usize,
+            ),
+            prop::collection::vec(arbitrary_edit(width, height), 1..=3),
+        )
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn reextraction_matches_full_extraction((mut grid, edits) in arbitrary_edits()) {
+        let mut simulator = extract_grid(&grid);
+
+        for Edit { rect, fill } in edits {
+            edit(&mut simulator, &mut grid, rect, &fill);
+            assert_matches_full_extraction(&simulator, &grid);
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Carrying the state of a circuit over to an edited version of its image.
+
+mod common;
+
+use bls_simulator::*;
+
+/// Gate pointing up from the input at (1, 3) to the output at (2, 0),
+/// and a separate wire along the bottom row.
+const CIRCUIT: [&str; 6] = [
+    "..#......",
+    "..#......",
+    ".#.#.....",
+    ".###.....",
+    ".........",
+    "#########",
+];
+const INPUT: (u32, u32) = (1, 3);
+const OUTPUT: (u32, u32) = (2, 0);
+const BOTTOM: (u32, u32) = (0, 5);
+
+/// The circuit settled with the input low, so the output is high, and the bottom wire set.
+fn running() -> Simulator {
+    let mut simulator = common::extract(&CIRCUIT)
+        .with_seed(5)
+        .with_timing(TimingModel::Unit);
+    assert!(simulator.set(BOTTOM.0, BOTTOM.1, true));
+    assert!(simulator.simulate_until_stable(100).is_stable());
+    assert_eq!(simulator.get(OUTPUT.0, OUTPUT.1), Some(true));
+
+    simulator
+}
+
+#[test]
+fn same_image_keeps_everything() {
+    let mut previous = running();
+    let mut simulator = common::extract(&CIRCUIT)
+        .with_seed(5)
+        .with_timing(TimingModel::Ideal);
+
+    let carry_over = simulator.carry_over(&previous);
+    assert_eq!(carry_over, CarryOver { wires: 3, gates: 1 });
+
+    assert_eq!(simulator.get(OUTPUT.0, OUTPUT.1), Some(true));
+    assert_eq!(simulator.get(BOTTOM.0, BOTTOM.1), Some(true));
+    assert_eq!(simulator.gates[0].state, previous.gates[0].state);
+    assert_eq!(simulator.gates[0].slow_state, previous.gates[0].slow_state);
+
+    assert_eq!(simulator.step_count(), previous.step_count());
+    assert_eq!(simulator.seed(), 5);
+    assert_eq!(simulator.timing(), TimingModel::Unit);
+
+    // Nothing changes, as if the image had never been reloaded.
+    for simulator in [&mut previous, &mut simulator] {
+        simulator.set(INPUT.0, INPUT.1, true);
+        simulator.simulate(3);
+    }
+    assert_eq!(
+        simulator.get(OUTPUT.0, OUTPUT.1),
+        previous.get(OUTPUT.0, OUTPUT.1)
+    );
+    assert_eq!(simulator.step_count(), previous.step_count());
+}
+
+#[test]
+fn edited_wires_start_low() {
+    let previous = running();
+
+    // Widen the output and shorten the bottom wire.
+    let mut edited = CIRCUIT;
+    edited[0] = "..##.....";
+    edited[5] = "########.";
+    let mut simulator = common::extract(&edited)
+        .with_seed(5)
+        .with_timing(TimingModel::Unit);
+
+    let carry_over = simulator.carry_over(&previous);
+    assert_eq!(carry_over, CarryOver { wires: 1, gates: 0 });
+
+    assert_eq!(simulator.get(OUTPUT.0, OUTPUT.1), Some(false));
+    assert_eq!(simulator.get(BOTTOM.0, BOTTOM.1), Some(false));
+    assert!(!simulator.gates[0].state);
+
+    // The gate picks up again from its unchanged input.
+    assert!(simulator.simulate_until_stable(100).is_stable());
+    assert_eq!(simulator.get(OUTPUT.0, OUTPUT.1), Some(true));
+}
+
+#[test]
+fn resized_image_keeps_wires_in_place() {
+    let previous = running();
+
+    let mut resized: Vec<String> = CIRCUIT.iter().map(|row| format!("{}..", row)).collect();
+    resized.push("...........".to_string());
+    resized.push("........##.".to_string());
+    let resized: Vec<&str> = resized.iter().map(String::as_str).collect();
+    let mut simulator = common::extract(&resized)
+        .with_seed(5)
+        .with_timing(TimingModel::Unit);
+
+    let carry_over = simulator.carry_over(&previous);
+    assert_eq!(carry_over, CarryOver { wires: 3, gates: 1 });
+    assert_eq!(simulator.wires.len(), 4);
+    assert_eq!(simulator.get(BOTTOM.0, BOTTOM.1), Some(true));
+    assert_eq!(simulator.get(8, 7), Some(false));
+}
+
+#[test]
+fn merged_wires_start_low() {
+    let previous = running();
+
+    // Join the input to the bottom wire.
+    let mut edited = CIRCUIT;
+    edited[4] = ".#.......";
+    let mut simulator = common::extract(&edited)
+        .with_seed(5)
+        .with_timing(TimingModel::Unit);
+
+    let carry_over = simulator.carry_over(&previous);
+    assert_eq!(carry_over, CarryOver { wires: 1, gates: 0 });
+    assert_eq!(simulator.get(BOTTOM.0, BOTTOM.1), Some(false));
+    assert_eq!(simulator.get(OUTPUT.0, OUTPUT.1), Some(true));
+}
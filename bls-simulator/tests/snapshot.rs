@@ -0,0 +1,121 @@
+//! Saving and restoring the state of golden circuits.
+
+mod common;
+
+use bls_simulator::*;
+use common::states;
+
+fn load(name: &str) -> Simulator {
+    common::load(name)
+        .with_seed(7)
+        .with_timing(TimingModel::default())
+}
+
+#[test]
+fn restored_run_continues_identically() {
+    for engine in [Engine::Sweep, Engine::EventDriven] {
+        let mut original = load("sr_latch").with_engine(engine);
+        original.set(2, 1, true);
+        original.simulate(15);
+
+        let text = serde_json::to_string(&original.snapshot()).unwrap();
+        let snapshot = Snapshot::parse(&text).unwrap();
+        assert_eq!(snapshot, original.snapshot());
+
+        // A different seed is replaced by the saved RNG state.
+        let mut restored = load("sr_latch").with_engine(engine).with_seed(99);
+        restored.restore(&snapshot).unwrap();
+        assert_eq!(restored.snapshot(), snapshot);
+        assert_eq!(states(&restored), states(&original));
+        assert_eq!(restored.step_count(), 15);
+        assert_eq!(restored.seed(), 7);
+
+        for _ in 0..40 {
+            original.simulate_one_step();
+            restored.simulate_one_step();
+            assert_eq!(states(&restored), states(&original), "{:?}", engine);
+        }
+        assert_eq!(restored.step_count(), 55);
+    }
+}
+
+#[test]
+fn save_and_load_file() {
+    let mut simulator = load("ring_oscillator");
+    simulator.simulate(5);
+
+    let path = std::env::temp_dir().join(format!("bls-snapshot-{}.json", std::process::id()));
+    simulator.snapshot().save(&path).unwrap();
+    let snapshot = Snapshot::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(snapshot, simulator.snapshot());
+    assert_eq!(snapshot.wires.len(), simulator.wires.len());
+}
+
+#[test]
+fn rejects_snapshot_of_another_image() {
+    let snapshot = load("ring_oscillator").snapshot();
+    let mut simulator = load("inverter_chain");
+
+    match simulator.restore(&snapshot) {
+        Err(SnapshotError::ImageMismatch { expected, found }) => {
+            assert_eq!(expected, simulator.image_hash());
+            assert_eq!(found, snapshot.image_hash);
+        }
+        result => panic!("expected an image mismatch, got {:?}", result),
+    }
+}
+
+#[test]
+fn rejects_malformed_state() {
+    let mut simulator = load("ring_oscillator");
+    simulator.simulate(3);
+    let before = states(&simulator);
+
+    let mut snapshot = simulator.snapshot();
+    snapshot.wires.push('1');
+    assert!(matches!(
+        simulator.restore(&snapshot),
+        Err(SnapshotError::Invalid(_))
+    ));
+
+    let mut snapshot = simulator.snapshot();
+    snapshot.wires.replace_range(0..1, "x");
+    assert!(matches!(
+        simulator.restore(&snapshot),
+        Err(SnapshotError::Invalid(_))
+    ));
+
+    let mut snapshot = simulator.snapshot();
+    snapshot.active_gates.push(3);
+    assert!(matches!(
+        simulator.restore(&snapshot),
+        Err(SnapshotError::Invalid(_))
+    ));
+
+    let mut snapshot = simulator.snapshot();
+    snapshot.gates.pop();
+    assert!(matches!(
+        simulator.restore(&snapshot),
+        Err(SnapshotError::Invalid(_))
+    ));
+
+    assert_eq!(states(&simulator), before);
+}
+
+#[test]
+fn image_hash_depends_on_every_pixel() {
+    let mut data = vec![0u8; 4 * 4 * 4];
+    let hash = image_hash(4, 4, 4, &data);
+
+    // Saved snapshots rely on the hash never changing.
+    assert_eq!(image_hash(0, 0, 4, &[]), 0xd47d_00b8_c439_7251);
+
+    data[37] = 255;
+    assert_ne!(image_hash(4, 4, 4, &data), hash);
+    assert_ne!(image_hash(2, 8, 4, &[0u8; 64]), hash);
+
+    let simulator = Simulator::from_pixels(4, 4, 4, &data);
+    assert_eq!(simulator.image_hash(), image_hash(4, 4, 4, &data));
+}
@@ -0,0 +1,74 @@
+//! Running golden circuits until they settle or oscillate.
+
+mod common;
+
+use bls_simulator::*;
+use common::load;
+
+#[test]
+fn inverter_chain_settles() {
+    let mut simulator = load("inverter_chain");
+
+    let stability = simulator.simulate_until_stable(100);
+    assert!(stability.is_stable(), "{:?}", stability);
+
+    // Changing the input ripples through three gates.
+    assert!(simulator.set(2, 1, true));
+    let stability = simulator.simulate_until_stable(100);
+    assert!(stability.is_stable(), "{:?}", stability);
+    assert!(stability.steps() <= 4, "{:?}", stability);
+    assert_eq!(simulator.get(14, 11), Some(false));
+
+    // Already settled.
+    assert_eq!(
+        simulator.simulate_until_stable(100),
+        Stability::Stable { steps: 0 }
+    );
+}
+
+#[test]
+fn settles_under_every_timing_model() {
+    for timing in [
+        TimingModel::Ideal,
+        TimingModel::Unit,
+        TimingModel::RiseFall { rise: 3, fall: 2 },
+        TimingModel::default(),
+    ] {
+        let mut simulator = load("half_adder").with_timing(timing);
+        simulator.set(2, 1, true);
+
+        let stability = simulator.simulate_until_stable(1000);
+        assert!(stability.is_stable(), "{:?}: {:?}", timing, stability);
+        assert_eq!(simulator.get(34, 39), Some(true), "{:?}", timing);
+    }
+}
+
+#[test]
+fn ring_oscillator_is_detected() {
+    let mut simulator = load("ring_oscillator");
+
+    match simulator.simulate_until_stable(100) {
+        Stability::Oscillating {
+            steps,
+            period,
+            wire_ids,
+        } => {
+            // Starting with every wire low, all three gates flip together.
+            assert_eq!(steps, 1);
+            assert_eq!(period, 2);
+            assert_eq!(wire_ids, [0, 1, 2]);
+        }
+        stability => panic!("expected an oscillation, got {:?}", stability),
+    }
+}
+
+#[test]
+fn gives_up_after_max_steps() {
+    let mut simulator = load("ring_oscillator");
+
+    assert_eq!(
+        simulator.simulate_until_stable(2),
+        Stability::Unsettled { steps: 2 }
+    );
+    assert_eq!(simulator.step_count(), 2);
+}
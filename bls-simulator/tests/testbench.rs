@@ -0,0 +1,136 @@
+//! Scripted test benches run against golden circuits.
+
+mod common;
+
+use bls_simulator::*;
+use common::{circuit_path, load_with_pins as load};
+
+fn run(name: &str, bench: &str) -> TestReport {
+    TestBench::parse(bench)
+        .unwrap()
+        .run(&mut load(name))
+        .unwrap()
+}
+
+#[test]
+fn half_adder_bench_passes() {
+    let path = circuit_path("half_adder.bench");
+    let bench = TestBench::parse(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+    for timing in [
+        TimingModel::Ideal,
+        TimingModel::Unit,
+        TimingModel::default(),
+    ] {
+        let mut simulator = load("half_adder").with_timing(timing);
+        let report = bench.run(&mut simulator).unwrap();
+
+        assert_eq!(report.failure, None, "{:?}", timing);
+        assert_eq!(report.passed, 11);
+    }
+}
+
+#[test]
+fn mismatch_reports_line_step_and_pixel() {
+    let report = run(
+        "half_adder",
+        "set a 1\nset b 0\nstep 20\nexpect carry 0\n\n# wrong\nexpect sum 0\nexpect carry 1\n",
+    );
+
+    assert!(!report.is_pass());
+    assert_eq!(report.passed, 1);
+    assert_eq!(
+        report.failure,
+        Some(Failure {
+            line: 7,
+            step: 20,
+            location: Some((34, 39)),
+            message: "sum at (34, 39) is 1, expected 0".to_string(),
+        })
+    );
+}
+
+#[test]
+fn bus_mismatch_points_at_the_first_wrong_bit() {
+    let report = run("half_adder", "set operands 3\nsettle\nexpect result 3\n");
+    let failure = report.failure.unwrap();
+
+    // result is 2, so bit 0 (sum) is wrong
+    assert_eq!(failure.location, Some((34, 39)));
+    assert_eq!(failure.message, "result at (34, 39) is 2, expected 3");
+}
+
+#[test]
+fn failing_table_row() {
+    let report = run(
+        "half_adder",
+        "table a b -> sum carry\n0 0 -> 0 0\n1 1 -> 1 1\nend\n",
+    );
+    let failure = report.failure.unwrap();
+
+    assert_eq!(report.passed, 2);
+    assert_eq!(failure.line, 3);
+    assert_eq!(failure.location, Some((34, 39)));
+}
+
+#[test]
+fn oscillation_fails_settle() {
+    let report = run("ring_oscillator", "settle 100\n");
+    let failure = report.failure.unwrap();
+
+    assert_eq!(failure.line, 1);
+    assert_eq!(failure.location, None);
+    assert_eq!(
+        failure.message,
+        "circuit oscillates with period 2 on 3 wires"
+    );
+
+    let report = run("ring_oscillator", "timeout 1\nsettle\n");
+    assert_eq!(
+        report.failure.unwrap().message,
+        "circuit is not stable after 1 steps"
+    );
+}
+
+#[test]
+fn parse_errors() {
+    let error = |text: &str| TestBench::parse(text).unwrap_err();
+
+    assert_eq!(error("step 1\nfrobnicate\n").line, 2);
+    assert_eq!(error("set a\n").line, 1);
+    assert_eq!(error("set a x\n").message, "invalid value \"x\"");
+    assert_eq!(error("set 1,y 1\n").message, "invalid coordinates \"1,y\"");
+    assert_eq!(error("table a b\nend\n").line, 1);
+    assert_eq!(
+        error("table a -> b\n0 -> 0\n").message,
+        "table is not closed by end"
+    );
+    assert_eq!(
+        error("table a -> b\n# comment\n0 1 -> 0\nend\n"),
+        ScriptError {
+            line: 3,
+            message: "expected 1 inputs and 1 outputs".to_string()
+        }
+    );
+}
+
+#[test]
+fn runtime_errors() {
+    let error = |text: &str| {
+        TestBench::parse(text)
+            .unwrap()
+            .run(&mut load("half_adder"))
+            .unwrap_err()
+    };
+
+    assert_eq!(error("set x 1\n").message, "no pin or bus named x");
+    assert_eq!(error("set 0,0 1\n").message, "(0, 0) is not on a wire");
+    assert_eq!(
+        error("set a 2\n").message,
+        "a is a single wire, expected 0 or 1"
+    );
+    assert_eq!(
+        error("set operands 4\n").message,
+        "4 does not fit in operands"
+    );
+}
@@ -0,0 +1,114 @@
+//! Delays of each timing model, on a single gate and on the inverter chain.
+
+mod common;
+
+use bls_simulator::*;
+use common::INVERTER_CHAIN;
+
+/// Steps after the input of the single gate changed to `input` until its output follows.
+fn steps_until_output(simulator: &mut Simulator, input: bool) -> u32 {
+    for steps in 1..=100 {
+        simulator.simulate_one_step();
+        if simulator.gates[0].state != input {
+            return steps;
+        }
+    }
+
+    panic!("gate never followed its input");
+}
+
+/// A single gate pointing up, its input wire around the bottom.
+fn single_gate(timing: TimingModel) -> Simulator {
+    let mut simulator = common::extract(&[".....", "..#..", ".#.#.", ".###.", "....."])
+        .with_seed(0)
+        .with_timing(timing);
+    assert_eq!(simulator.gates.len(), 1);
+
+    // Settle with the input low.
+    simulator.simulate(100);
+    assert!(simulator.gates[0].state);
+    simulator
+}
+
+/// Steps the inverter chain output takes to follow a change of its input.
+fn chain_delay(timing: TimingModel, input: bool) -> u32 {
+    const IN: (u32, u32) = (2, 1);
+    const OUT: (u32, u32) = (14, 11);
+
+    let mut simulator = common::extract(&INVERTER_CHAIN)
+        .with_seed(0)
+        .with_timing(timing);
+    simulator.set(IN.0, IN.1, !input);
+    simulator.simulate(100);
+
+    let out = simulator.wire_map[OUT.1 as usize][OUT.0 as usize] as u32;
+    simulator.set(IN.0, IN.1, input);
+    for steps in 1..=100 {
+        simulator.simulate_one_step();
+        if simulator.get_wire_state(out) != input {
+            return steps;
+        }
+    }
+
+    panic!("output never followed the input");
+}
+
+#[test]
+fn ideal_settles_within_the_step() {
+    for input in [true, false] {
+        assert_eq!(chain_delay(TimingModel::Ideal, input), 1);
+    }
+
+    let mut simulator = single_gate(TimingModel::Ideal);
+    simulator.set(2, 3, true);
+    assert_eq!(steps_until_output(&mut simulator, true), 1);
+}
+
+#[test]
+fn unit_takes_one_step_per_gate() {
+    for input in [true, false] {
+        assert_eq!(chain_delay(TimingModel::Unit, input), 3);
+    }
+
+    let mut simulator = single_gate(TimingModel::Unit);
+    simulator.set(2, 3, true);
+    assert_eq!(steps_until_output(&mut simulator, true), 1);
+    simulator.set(2, 3, false);
+    assert_eq!(steps_until_output(&mut simulator, false), 1);
+}
+
+#[test]
+fn rise_and_fall_take_their_own_number_of_steps() {
+    for (rise, fall) in [(1, 1), (4, 2), (2, 5), (7, 3)] {
+        let timing = TimingModel::RiseFall { rise, fall };
+        let mut simulator = single_gate(timing);
+
+        // A high input makes the output fall.
+        simulator.set(2, 3, true);
+        assert_eq!(
+            steps_until_output(&mut simulator, true),
+            fall,
+            "{:?}",
+            timing
+        );
+        simulator.set(2, 3, false);
+        assert_eq!(
+            steps_until_output(&mut simulator, false),
+            rise,
+            "{:?}",
+            timing
+        );
+    }
+
+    // Through three gates, a rising input makes the output fall, rise and fall.
+    let timing = TimingModel::RiseFall { rise: 4, fall: 2 };
+    assert_eq!(chain_delay(timing, true), 2 + 4 + 2);
+    assert_eq!(chain_delay(timing, false), 4 + 2 + 4);
+}
+
+#[test]
+fn delays_below_one_step_take_one_step() {
+    let mut simulator = single_gate(TimingModel::RiseFall { rise: 0, fall: 0 });
+    simulator.set(2, 3, true);
+    assert_eq!(steps_until_output(&mut simulator, true), 1);
+}
@@ -0,0 +1,111 @@
+//! Truth tables of golden circuits and their minimized expressions.
+
+mod common;
+
+use bls_simulator::*;
+use common::load_with_pins as load;
+
+/// Table of `inputs` with a single output `f`, `None` for `-`.
+fn table(inputs: &[&str], outputs: &[Option<bool>]) -> TruthTable {
+    TruthTable {
+        inputs: inputs.iter().map(|name| name.to_string()).collect(),
+        outputs: vec!["f".to_string()],
+        rows: outputs
+            .iter()
+            .enumerate()
+            .map(|(combination, output)| TruthTableRow {
+                inputs: combination as u64,
+                outputs: output.map(|output| vec![output]),
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn half_adder_table() {
+    let table = load("half_adder")
+        .truth_table(&["a", "b"], &["sum", "carry"], 1000)
+        .unwrap();
+
+    let rows: Vec<(u64, Option<Vec<bool>>)> = table
+        .rows
+        .iter()
+        .map(|row| (row.inputs, row.outputs.clone()))
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            (0, Some(vec![false, false])),
+            (1, Some(vec![true, false])),
+            (2, Some(vec![true, false])),
+            (3, Some(vec![false, true])),
+        ]
+    );
+    assert_eq!(table.unsettled().count(), 0);
+
+    assert_eq!(table.minimized(0), "!a & b | a & !b");
+    assert_eq!(table.minimized(1), "a & b");
+
+    let mut text = Vec::new();
+    table.write(&mut text).unwrap();
+    assert_eq!(
+        String::from_utf8(text).unwrap(),
+        "a b | sum carry\n\
+         0 0 | 0   0\n\
+         0 1 | 1   0\n\
+         1 0 | 1   0\n\
+         1 1 | 0   1\n"
+    );
+}
+
+#[test]
+fn oscillating_rows_are_flagged() {
+    let mut simulator = load("ring_oscillator");
+    let file = PinFile::parse("[inputs]\nen = [2, 5]\n\n[outputs]\nq = [10, 9]\n").unwrap();
+    simulator.load_pins(&file).unwrap();
+
+    let table = simulator.truth_table(&["en"], &["q"], 100).unwrap();
+
+    assert_eq!(table.unsettled().count(), 2);
+    assert_eq!(table.minimized(0), "0");
+
+    let mut text = Vec::new();
+    table.write(&mut text).unwrap();
+    assert_eq!(
+        String::from_utf8(text).unwrap(),
+        "en | q\n\
+         0  | -  # never settles\n\
+         1  | -  # never settles\n"
+    );
+}
+
+#[test]
+fn rejects_unknown_pins_and_too_many_inputs() {
+    let mut simulator = load("half_adder");
+
+    assert_eq!(
+        simulator.truth_table(&["a", "c"], &["sum"], 1000),
+        Err(TruthTableError::UnknownPin("c".to_string()))
+    );
+    assert_eq!(
+        simulator.truth_table(&["a"; 17], &["sum"], 1000),
+        Err(TruthTableError::TooManyInputs(17))
+    );
+}
+
+#[test]
+fn minimizes_with_dont_cares() {
+    let t = Some(true);
+    let f = Some(false);
+
+    // Majority of three.
+    let majority = table(&["a", "b", "c"], &[f, f, f, t, f, t, t, t]);
+    assert_eq!(majority.minimized(0), "a & b | a & c | b & c");
+
+    // Unsettled rows are free to make the expression smaller.
+    let and_or_dont_care = table(&["a", "b"], &[f, None, f, t]);
+    assert_eq!(and_or_dont_care.minimized(0), "b");
+
+    assert_eq!(table(&["a", "b"], &[t, t, None, t]).minimized(0), "1");
+    assert_eq!(table(&["a", "b"], &[f, f, f, f]).minimized(0), "0");
+}
@@ -0,0 +1,148 @@
+//! Waveforms of probed wires written as Value Change Dumps.
+
+mod common;
+
+use bls_simulator::*;
+
+/// States of wires by id, as the recorder samples them.
+fn wires(states: &'static [bool]) -> impl Fn(u32) -> bool {
+    move |wire_id| states[wire_id as usize]
+}
+
+fn probe(name: &str, wire_id: u32) -> WireProbe {
+    WireProbe {
+        name: name.to_string(),
+        x: 0,
+        y: 0,
+        wire_id,
+    }
+}
+
+fn vcd(recorder: &VcdRecorder, end_step: u64) -> String {
+    let mut out = Vec::new();
+    recorder.write(&mut out, end_step).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn header_initial_values_and_changes() {
+    let mut recorder = VcdRecorder::new();
+    recorder.add_probe(probe("a", 0), 0, wires(&[false, true]));
+    recorder.add_probe(probe("b", 1), 0, wires(&[false, true]));
+
+    recorder.sample(1, wires(&[true, true]));
+    recorder.sample(2, wires(&[true, true]));
+    recorder.sample(3, wires(&[false, false]));
+
+    assert_eq!(
+        vcd(&recorder, 5),
+        "\
+$version bls-rs $end
+$timescale 1ns $end
+$scope module circuit $end
+$var wire 1 ! a $end
+$var wire 1 \" b $end
+$upscope $end
+$enddefinitions $end
+#0
+$dumpvars
+0!
+1\"
+$end
+#1
+1!
+#3
+0!
+0\"
+#5
+"
+    );
+}
+
+#[test]
+fn probes_added_later_are_unknown_until_sampled() {
+    let mut recorder = VcdRecorder::new();
+    recorder.add_probe(probe("a", 0), 2, wires(&[true, false]));
+    recorder.sample(3, wires(&[false, false]));
+    recorder.add_probe(probe("b", 1), 4, wires(&[false, true]));
+
+    let vcd = vcd(&recorder, 4);
+    let body = vcd.split("$enddefinitions $end\n").nth(1).unwrap();
+    assert_eq!(body, "#2\n$dumpvars\n1!\nx\"\n$end\n#3\n0!\n#4\n1\"\n");
+}
+
+#[test]
+fn final_timestamp_is_written_once() {
+    let mut recorder = VcdRecorder::new();
+    recorder.add_probe(probe("a", 0), 0, wires(&[false]));
+    recorder.sample(4, wires(&[true]));
+
+    // No timestamp is added when the waveform already ends at the last step.
+    assert!(vcd(&recorder, 4).ends_with("$end\n#4\n1!\n"));
+    // Nor does an end before the last change.
+    assert!(vcd(&recorder, 0).ends_with("$end\n#4\n1!\n"));
+    assert!(vcd(&recorder, 9).ends_with("#4\n1!\n#9\n"));
+
+    let empty = vcd(&VcdRecorder::new(), 3);
+    assert!(empty.ends_with("$enddefinitions $end\n#0\n$dumpvars\n$end\n#3\n"));
+}
+
+#[test]
+fn identifiers_are_unique_and_printable() {
+    let count = 94 * 95 + 10;
+    let mut recorder = VcdRecorder::new();
+    for probe_id in 0..count {
+        recorder.add_probe(probe(&format!("w {}", probe_id), 0), 0, wires(&[false]));
+    }
+
+    let vcd = vcd(&recorder, 0);
+    let vars: Vec<Vec<&str>> = vcd
+        .lines()
+        .filter(|line| line.starts_with("$var"))
+        .map(|line| line.split(' ').collect())
+        .collect();
+    assert_eq!(vars.len(), count);
+
+    let mut identifiers: Vec<&str> = vars.iter().map(|var| var[3]).collect();
+    assert_eq!(identifiers[..3], ["!", "\"", "#"]);
+    assert_eq!(identifiers[93..96], ["~", "!!", "\"!"]);
+    assert!(identifiers
+        .iter()
+        .all(|identifier| identifier.bytes().all(|c| (b'!'..=b'~').contains(&c))));
+    identifiers.sort_unstable();
+    identifiers.dedup();
+    assert_eq!(identifiers.len(), count);
+
+    // Whitespace in names would split the declaration.
+    assert_eq!(vars[7][4], "w_7");
+    assert_eq!(vars[7].len(), 6);
+}
+
+#[test]
+fn simulator_records_probed_wires() {
+    const IN: (u32, u32) = (2, 1);
+    const OUT: (u32, u32) = (14, 11);
+
+    let mut simulator = common::extract(&common::INVERTER_CHAIN)
+        .with_seed(0)
+        .with_timing(TimingModel::Unit);
+    simulator.simulate(10);
+    assert!(simulator.add_probe("in", IN.0, IN.1));
+    assert!(simulator.add_probe("out", OUT.0, OUT.1));
+    assert!(!simulator.add_probe("nothing", 0, 0));
+
+    assert!(simulator.set(IN.0, IN.1, true));
+    simulator.simulate(10);
+
+    let mut out = Vec::new();
+    simulator.write_vcd(&mut out).unwrap();
+    let vcd = String::from_utf8(out).unwrap();
+    let body = vcd.split("$enddefinitions $end\n").nth(1).unwrap();
+
+    // The input is seen on the next step, and the output as soon as the last of the
+    // three gates switches.
+    assert_eq!(
+        body,
+        "#10\n$dumpvars\n0!\n1\"\n$end\n#11\n1!\n#13\n0\"\n#20\n"
+    );
+}
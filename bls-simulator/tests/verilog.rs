@@ -0,0 +1,166 @@
+//! Structural Verilog modules written from extracted netlists.
+
+mod common;
+
+use std::io;
+
+use bls_simulator::*;
+
+/// A gate pointing right from wire 1 to wire 0, and a lone pixel, wire 2.
+fn single_gate() -> Netlist {
+    common::extract(&["##...", "#.##.", "##...", ".....", "....#"]).netlist()
+}
+
+fn port(name: &str, wire_id: u32, direction: PortDirection) -> VerilogPort {
+    VerilogPort {
+        name: name.to_string(),
+        wire_id,
+        direction,
+    }
+}
+
+fn verilog(netlist: &Netlist, ports: Vec<VerilogPort>) -> io::Result<String> {
+    let options = VerilogOptions {
+        ports,
+        ..Default::default()
+    };
+    let mut out = Vec::new();
+    netlist.write_verilog(&mut out, &options)?;
+
+    Ok(String::from_utf8(out).unwrap())
+}
+
+#[test]
+fn module() {
+    let ports = vec![
+        port("in", 1, PortDirection::Input),
+        port("out", 0, PortDirection::Output),
+    ];
+
+    assert_eq!(
+        verilog(&single_gate(), ports).unwrap(),
+        "\
+module circuit(in, out);
+    input in;
+    output out;
+
+    wire _n_w0;
+    wire _n_w1;
+    wire _n_w2;
+    wire _n_g0;
+
+    not #1 not0 (_n_g0, _n_w1); // (1, 1) right
+
+    assign _n_w0 = _n_g0;
+    assign _n_w1 = in;
+    assign _n_w2 = 1'b0;
+    assign out = _n_w0;
+endmodule
+"
+    );
+}
+
+#[test]
+fn no_delay() {
+    let options = VerilogOptions {
+        module_name: "inverter".to_string(),
+        gate_delay: 0,
+        ..Default::default()
+    };
+    let mut out = Vec::new();
+    single_gate().write_verilog(&mut out, &options).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    assert!(out.starts_with("module inverter();\n"));
+    assert!(out.contains("    not not0 (_n_g0, _n_w1);"));
+}
+
+#[test]
+fn port_names_do_not_clash_with_nets() {
+    let ports = vec![
+        port("w1", 1, PortDirection::Input),
+        port("g0", 0, PortDirection::Output),
+    ];
+    let out = verilog(&single_gate(), ports).unwrap();
+
+    assert!(out.contains("    input w1;\n    output g0;\n"));
+    assert!(out.contains("    assign _n_w1 = w1;\n"));
+    assert!(out.contains("    assign g0 = _n_w0;\n"));
+
+    let ports = vec![port("_n_w2", 2, PortDirection::Input)];
+    assert_eq!(
+        verilog(&single_gate(), ports).unwrap_err().kind(),
+        io::ErrorKind::InvalidInput
+    );
+}
+
+#[test]
+fn names_are_made_identifiers() {
+    let ports = vec![
+        port("input", 1, PortDirection::Input),
+        port("sum[0]", 0, PortDirection::Output),
+        port("2nd", 2, PortDirection::Output),
+    ];
+    let out = verilog(&single_gate(), ports).unwrap();
+
+    assert!(out.starts_with("module circuit(\\input , sum_0_, _2nd);\n"));
+    assert!(out.contains("    input \\input ;\n"));
+    assert!(out.contains("    assign _n_w1 = \\input ;\n"));
+    assert!(out.contains("    output sum_0_;\n"));
+    assert!(out.contains("    assign _2nd = _n_w2;\n"));
+
+    let options = VerilogOptions {
+        module_name: "module".to_string(),
+        ..Default::default()
+    };
+    let mut out = Vec::new();
+    single_gate().write_verilog(&mut out, &options).unwrap();
+    assert!(String::from_utf8(out)
+        .unwrap()
+        .starts_with("module \\module ();\n"));
+}
+
+#[test]
+fn invalid_ports() {
+    for ports in [
+        // Two names made the same identifier.
+        vec![
+            port("a b", 1, PortDirection::Input),
+            port("a_b", 0, PortDirection::Output),
+        ],
+        vec![
+            port("out", 0, PortDirection::Output),
+            port("out", 2, PortDirection::Output),
+        ],
+        // Driven by the gate.
+        vec![port("in", 0, PortDirection::Input)],
+        // No such wire.
+        vec![port("in", 3, PortDirection::Input)],
+    ] {
+        let err = verilog(&single_gate(), ports).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{}", err);
+    }
+}
+
+#[test]
+fn input_ports_sharing_a_wire_all_drive_it() {
+    // Pins a and b are also the bits of the operands bus.
+    let simulator = common::load_with_pins("half_adder");
+    let pins = simulator.pins();
+    let ports: Vec<VerilogPort> = pins
+        .pins
+        .iter()
+        .chain(pins.buses.iter().flat_map(|bus| &bus.bits))
+        .map(|pin| port(&pin.name, pin.wire_id, pin.direction))
+        .collect();
+    let out = verilog(&simulator.netlist(), ports).unwrap();
+
+    let a = pins.pin("a").unwrap().wire_id;
+    let b = pins.pin("b").unwrap().wire_id;
+    assert!(out.contains(&format!("    assign _n_w{} = a | operands_0_;\n", a)));
+    assert!(out.contains(&format!("    assign _n_w{} = b | operands_1_;\n", b)));
+
+    let carry = pins.pin("carry").unwrap().wire_id;
+    assert!(out.contains(&format!("    assign carry = _n_w{};\n", carry)));
+    assert!(out.contains(&format!("    assign result_1_ = _n_w{};\n", carry)));
+}
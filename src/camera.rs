@@ -12,6 +12,10 @@ struct MouseSystem {
     prev_cursor_pos: Option<Vec2>,
 }
 
+/// Position of the cursor in the world, if it is over the window.
+#[derive(Resource, Default)]
+pub struct WorldCursor(pub Option<Vec2>);
+
 pub struct WorldClickEvent {
     pub pos: Vec2,
     pub state: ButtonState,
@@ -24,6 +28,7 @@ impl Plugin for CameraPlugin {
         app.insert_resource(MouseSystem {
             prev_cursor_pos: None,
         })
+        .init_resource::<WorldCursor>()
         .add_event::<WorldClickEvent>()
         .add_startup_system(setup)
         .add_system(mouse_click_system);
@@ -48,6 +53,7 @@ fn mouse_click_system(
     mut cursor_moved_events: EventReader<CursorMoved>,
     mut mouse_wheel_events: EventReader<MouseWheel>,
     mut ev_world_click: EventWriter<WorldClickEvent>,
+    mut world_cursor: ResMut<WorldCursor>,
 ) {
     let camera_entity = camera_entity_query.single_mut();
     let mut camera_transform = camera_entity.0;
@@ -104,4 +110,9 @@ fn mouse_click_system(
             .max(Vec3::new(0.1, 0.1, 1.0))
             .min(Vec3::new(100.0, 100.0, 1.0));
     }
+
+    world_cursor.0 = mouse_system
+        .prev_cursor_pos
+        .and_then(|pos| camera.viewport_to_world(camera_global_trans, pos))
+        .map(|ray| ray.origin.xy());
 }
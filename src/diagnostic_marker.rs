@@ -0,0 +1,132 @@
+use bevy::{
+    prelude::*,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+use bls_simulator::{diagnostics_report, Severity};
+
+use crate::{hot_reload::CircuitReloadEvent, CircuitComponent};
+
+/// Key toggling the markers.
+const TOGGLE_KEY: KeyCode = KeyCode::L;
+
+#[derive(Component)]
+struct DiagnosticMarker;
+
+#[derive(Component)]
+struct DiagnosticMarkersSpawned;
+
+/// Whether the markers are toggled off, which markers spawned later follow.
+#[derive(Resource, Default)]
+struct DiagnosticMarkers {
+    hidden: bool,
+}
+
+/// Logs extraction diagnostics and highlights warnings and errors on the circuit.
+pub struct DiagnosticMarkerPlugin;
+
+impl Plugin for DiagnosticMarkerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DiagnosticMarkers>()
+            .add_system(diagnostic_marker_spawn_system)
+            .add_system(diagnostic_marker_reload_system)
+            .add_system(diagnostic_marker_toggle_system);
+    }
+}
+
+fn diagnostic_marker_spawn_system(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut color_materials: ResMut<Assets<ColorMaterial>>,
+    markers: Res<DiagnosticMarkers>,
+    circuit_query: Query<(Entity, &CircuitComponent), Without<DiagnosticMarkersSpawned>>,
+) {
+    for (entity, circuit) in circuit_query.iter() {
+        let simulator = if let Some(simulator) = &circuit.simulator {
+            simulator
+        } else {
+            continue;
+        };
+
+        info!(
+            "circuit diagnostics:\n{}",
+            diagnostics_report(&simulator.diagnostics)
+        );
+
+        let mesh: Mesh2dHandle = meshes
+            .add(Mesh::from(shape::Quad::new(Vec2::new(3.0, 3.0))))
+            .into();
+        let error_material =
+            color_materials.add(ColorMaterial::from(Color::rgba(1.0, 0.0, 0.0, 0.6)));
+        let warning_material =
+            color_materials.add(ColorMaterial::from(Color::rgba(1.0, 0.8, 0.0, 0.6)));
+
+        let half_size = Vec2::new(simulator.width as f32, simulator.height as f32) / 2.0;
+
+        commands
+            .entity(entity)
+            .insert(DiagnosticMarkersSpawned)
+            .with_children(|parent| {
+                for diagnostic in &simulator.diagnostics {
+                    let material = match diagnostic.kind.severity() {
+                        Severity::Error => error_material.clone(),
+                        Severity::Warning => warning_material.clone(),
+                        Severity::Info => continue,
+                    };
+
+                    // Image rows go down while world y goes up.
+                    let translation = Vec3::new(
+                        diagnostic.x as f32 + 0.5 - half_size.x,
+                        half_size.y - diagnostic.y as f32 - 0.5,
+                        0.5,
+                    );
+
+                    parent.spawn((
+                        DiagnosticMarker,
+                        MaterialMesh2dBundle {
+                            mesh: mesh.clone(),
+                            material,
+                            transform: Transform::from_translation(translation),
+                            visibility: Visibility {
+                                is_visible: !markers.hidden,
+                            },
+                            ..default()
+                        },
+                    ));
+                }
+            });
+    }
+}
+
+/// Reloaded circuits are diagnosed and marked again.
+fn diagnostic_marker_reload_system(
+    mut commands: Commands,
+    mut ev_reload: EventReader<CircuitReloadEvent>,
+    marker_query: Query<(Entity, &Parent), With<DiagnosticMarker>>,
+) {
+    for event in ev_reload.iter() {
+        for (marker, parent) in marker_query.iter() {
+            if parent.get() == event.entity {
+                commands.entity(marker).despawn_recursive();
+            }
+        }
+
+        commands
+            .entity(event.entity)
+            .remove::<DiagnosticMarkersSpawned>();
+    }
+}
+
+fn diagnostic_marker_toggle_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut markers: ResMut<DiagnosticMarkers>,
+    mut marker_query: Query<&mut Visibility, With<DiagnosticMarker>>,
+) {
+    if !keyboard_input.just_pressed(TOGGLE_KEY) {
+        return;
+    }
+
+    markers.hidden = !markers.hidden;
+    marker_query.for_each_mut(|mut visibility| {
+        visibility.is_visible = !markers.hidden;
+    });
+}
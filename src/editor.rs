@@ -0,0 +1,467 @@
+use bevy::{
+    math::Vec3Swizzles,
+    prelude::*,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+use bls_simulator::{Canvas, Clip, PixelBuffer, PixelRect, Rgba, Simulator};
+
+use crate::{
+    camera::WorldCursor,
+    hot_reload::{modified_time, CircuitReloadEvent},
+    CircuitComponent, CircuitMaterial,
+};
+
+const EDIT_KEY: KeyCode = KeyCode::E;
+const TOOL_KEYS: [(KeyCode, Tool); 5] = [
+    (KeyCode::Key1, Tool::Pencil),
+    (KeyCode::Key2, Tool::Eraser),
+    (KeyCode::Key3, Tool::Line),
+    (KeyCode::Key4, Tool::Rect),
+    (KeyCode::Key5, Tool::Select),
+];
+const DESELECT_KEY: KeyCode = KeyCode::Escape;
+const ERASE_SELECTION_KEY: KeyCode = KeyCode::Delete;
+// Used with control.
+const UNDO_KEY: KeyCode = KeyCode::Z;
+const REDO_KEY: KeyCode = KeyCode::Y;
+const COPY_KEY: KeyCode = KeyCode::C;
+const PASTE_KEY: KeyCode = KeyCode::V;
+const SAVE_KEY: KeyCode = KeyCode::S;
+
+/// Drawn with the left mouse button, a wire for the default classifier.
+const WIRE_COLOR: Rgba = [255, 255, 255, 255];
+/// Drawn with the right mouse button and the eraser.
+const EMPTY_COLOR: Rgba = [0, 0, 0, 255];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Tool {
+    #[default]
+    Pencil,
+    Eraser,
+    Line,
+    Rect,
+    Select,
+}
+
+impl Tool {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Pencil => "pencil",
+            Self::Eraser => "eraser",
+            Self::Line => "line",
+            Self::Rect => "rectangle fill",
+            Self::Select => "select",
+        }
+    }
+}
+
+/// A mouse button held down over a circuit.
+struct Drag {
+    entity: Entity,
+    button: MouseButton,
+    color: Rgba,
+    start: (u32, u32),
+    last: (u32, u32),
+    /// Whether anything was drawn yet, pixels are only drawn again when the cursor moves.
+    drawn: bool,
+}
+
+/// Edit mode, drawing on the circuits with the mouse instead of toggling wires.
+#[derive(Resource, Default)]
+pub struct Editor {
+    editing: bool,
+    tool: Tool,
+    drag: Option<Drag>,
+    /// Circuit edited last, which undo, redo and save apply to.
+    active: Option<Entity>,
+    selection: Option<(Entity, PixelRect)>,
+    clip: Option<Clip>,
+}
+
+impl Editor {
+    pub fn is_editing(&self) -> bool {
+        self.editing
+    }
+
+    /// Line shown in the HUD while editing.
+    pub fn status(&self) -> Option<String> {
+        if !self.editing {
+            return None;
+        }
+
+        let mut status = format!("editing  {}", self.tool.name());
+        if let Some((_, rect)) = self.selection {
+            status += &format!("  selection {}x{}", rect.width, rect.height);
+        }
+
+        Some(status)
+    }
+}
+
+#[derive(Component)]
+struct SelectionMarker;
+
+/// Draws on the circuit textures, extracting the edited part of the circuit again.
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Editor>()
+            .add_startup_system(selection_marker_setup)
+            .add_system(editor_system)
+            .add_system(selection_marker_system);
+    }
+}
+
+/// Pixel of the circuit under the world position `pos`.
+/// Outside the image, returns None unless `clamp` moves it to the nearest pixel.
+fn pixel_at(
+    transform: &Transform,
+    simulator: &Simulator,
+    pos: Vec2,
+    clamp: bool,
+) -> Option<(u32, u32)> {
+    if simulator.width == 0 || simulator.height == 0 {
+        return None;
+    }
+
+    let size = Vec2::new(simulator.width as f32, simulator.height as f32);
+    let relative = pos - (transform.translation.xy() - size / 2.0);
+    // Image rows go down while world y goes up.
+    let mut pixel = Vec2::new(relative.x, size.y - relative.y).floor();
+
+    if clamp {
+        pixel = pixel.clamp(Vec2::ZERO, size - 1.0);
+    } else if pixel.x < 0.0 || pixel.y < 0.0 || pixel.x >= size.x || pixel.y >= size.y {
+        return None;
+    }
+
+    Some((pixel.x as u32, pixel.y as u32))
+}
+
+/// Canvas of the circuit, made from its texture the first time it is edited.
+fn canvas_of<'a>(
+    circuit: &'a mut CircuitComponent,
+    handle: &Handle<CircuitMaterial>,
+    circuit_materials: &Assets<CircuitMaterial>,
+    images: &Assets<Image>,
+) -> Option<&'a mut Canvas> {
+    if circuit.canvas.is_none() {
+        // The texture is 8-bit RGBA once the simulator was built from it.
+        let simulator = circuit.simulator.as_ref()?;
+        let image = images.get(&circuit_materials.get(handle)?.texture)?;
+        if image.data.len() != simulator.width as usize * simulator.height as usize * 4 {
+            return None;
+        }
+
+        circuit.canvas = Some(Canvas::new(PixelBuffer {
+            width: simulator.width,
+            height: simulator.height,
+            components: 4,
+            data: image.data.clone(),
+        }));
+    }
+
+    circuit.canvas.as_mut()
+}
+
+fn editor_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    cursor: Res<WorldCursor>,
+    mut editor: ResMut<Editor>,
+    mut ev_reload: EventWriter<CircuitReloadEvent>,
+    mut images: ResMut<Assets<Image>>,
+    circuit_materials: Res<Assets<CircuitMaterial>>,
+    mut circuit_query: Query<(
+        Entity,
+        &mut CircuitComponent,
+        &Handle<CircuitMaterial>,
+        &Transform,
+    )>,
+) {
+    let editor = &mut *editor;
+
+    if keyboard_input.just_pressed(EDIT_KEY) {
+        editor.editing = !editor.editing;
+
+        if !editor.editing {
+            editor.drag = None;
+            circuit_query.for_each_mut(|(_, mut circuit, _, _)| {
+                if let Some(canvas) = circuit.canvas.as_mut() {
+                    canvas.commit();
+                }
+            });
+        }
+    }
+
+    if !editor.editing {
+        return;
+    }
+
+    for (key, tool) in TOOL_KEYS {
+        if keyboard_input.just_pressed(key) {
+            editor.tool = tool;
+        }
+    }
+
+    let ctrl = keyboard_input.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    let shift = keyboard_input.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+
+    // Circuit and pixel under the cursor.
+    let hovered = cursor.0.and_then(|pos| {
+        circuit_query
+            .iter()
+            .find_map(|(entity, circuit, _, transform)| {
+                let simulator = circuit.simulator.as_ref()?;
+                Some((entity, pixel_at(transform, simulator, pos, false)?))
+            })
+    });
+
+    // Rectangles changed on every circuit, to show and extract again.
+    let mut edits: Vec<(Entity, PixelRect)> = Vec::new();
+    // Circuits done being edited for now, whose gate arrows and markers are placed again.
+    let mut finished: Vec<Entity> = Vec::new();
+
+    if keyboard_input.just_pressed(DESELECT_KEY) {
+        editor.selection = None;
+    }
+
+    if let Some((entity, rect)) = editor.selection {
+        if let Ok((_, mut circuit, handle, _)) = circuit_query.get_mut(entity) {
+            if let Some(canvas) = canvas_of(&mut circuit, handle, &circuit_materials, &images) {
+                if ctrl && keyboard_input.just_pressed(COPY_KEY) {
+                    editor.clip = Some(canvas.copy(rect));
+                }
+
+                if keyboard_input.just_pressed(ERASE_SELECTION_KEY) {
+                    edits.push((entity, canvas.fill_rect(rect, EMPTY_COLOR)));
+                    canvas.commit();
+                    finished.push(entity);
+                    editor.active = Some(entity);
+                }
+            }
+        }
+    }
+
+    if ctrl && keyboard_input.just_pressed(PASTE_KEY) {
+        if let (Some(clip), Some((entity, (x, y)))) = (&editor.clip, hovered) {
+            if let Ok((_, mut circuit, handle, _)) = circuit_query.get_mut(entity) {
+                if let Some(canvas) = canvas_of(&mut circuit, handle, &circuit_materials, &images) {
+                    let rect = canvas.paste(clip, x, y);
+                    canvas.commit();
+
+                    edits.push((entity, rect));
+                    finished.push(entity);
+                    editor.selection = Some((entity, rect));
+                    editor.active = Some(entity);
+                }
+            }
+        }
+    }
+
+    // Undo, redo and save apply to the circuit edited last.
+    if let Some(Ok((entity, mut circuit, _, _))) = editor.active.map(|e| circuit_query.get_mut(e)) {
+        let undo = ctrl && keyboard_input.just_pressed(UNDO_KEY);
+        let redo = ctrl && keyboard_input.just_pressed(REDO_KEY);
+
+        if let Some(canvas) = circuit.canvas.as_mut() {
+            let rect = if undo && !shift {
+                canvas.undo()
+            } else if redo || (undo && shift) {
+                canvas.redo()
+            } else {
+                None
+            };
+
+            if let Some(rect) = rect {
+                edits.push((entity, rect));
+                finished.push(entity);
+            }
+        }
+
+        if ctrl && keyboard_input.just_pressed(SAVE_KEY) {
+            if let Some(canvas) = circuit.canvas.as_mut() {
+                canvas.commit();
+            }
+
+            let saved = circuit
+                .canvas
+                .as_ref()
+                .map(|canvas| canvas.save(&circuit.path));
+            match saved {
+                Some(Ok(())) => {
+                    // Don't reload what was just saved.
+                    circuit.modified = modified_time(&circuit.path);
+                    info!("saved {}", circuit.path.display());
+                }
+                Some(Err(err)) => error!("failed to save {}: {}", circuit.path.display(), err),
+                None => {}
+            }
+        }
+    }
+
+    if editor.drag.is_none() && !ctrl {
+        let button = [MouseButton::Left, MouseButton::Right]
+            .into_iter()
+            .find(|&button| mouse_input.just_pressed(button));
+
+        if let (Some(button), Some((entity, pixel))) = (button, hovered) {
+            let color = if button == MouseButton::Right || editor.tool == Tool::Eraser {
+                EMPTY_COLOR
+            } else {
+                WIRE_COLOR
+            };
+
+            editor.drag = Some(Drag {
+                entity,
+                button,
+                color,
+                start: pixel,
+                last: pixel,
+                drawn: false,
+            });
+        }
+    }
+
+    if let Some(drag) = editor.drag.as_mut() {
+        let released = !mouse_input.pressed(drag.button);
+
+        if let Ok((entity, mut circuit, handle, transform)) = circuit_query.get_mut(drag.entity) {
+            let pixel = match (cursor.0, &circuit.simulator) {
+                (Some(pos), Some(simulator)) => pixel_at(transform, simulator, pos, true),
+                _ => None,
+            }
+            .unwrap_or(drag.last);
+
+            if editor.tool == Tool::Select {
+                editor.selection = Some((entity, PixelRect::from_corners(drag.start, pixel)));
+            } else if let Some(canvas) =
+                canvas_of(&mut circuit, handle, &circuit_materials, &images)
+            {
+                if !drag.drawn || pixel != drag.last {
+                    let rect = match editor.tool {
+                        Tool::Pencil | Tool::Eraser => canvas.line(drag.last, pixel, drag.color),
+                        // Lines and rectangles follow the cursor until the button is released.
+                        Tool::Line => canvas
+                            .revert()
+                            .union(canvas.line(drag.start, pixel, drag.color)),
+                        Tool::Rect => canvas.revert().union(
+                            canvas
+                                .fill_rect(PixelRect::from_corners(drag.start, pixel), drag.color),
+                        ),
+                        Tool::Select => PixelRect::default(),
+                    };
+
+                    edits.push((entity, rect));
+                    drag.drawn = true;
+                }
+
+                if released {
+                    canvas.commit();
+                    finished.push(entity);
+                }
+                editor.active = Some(entity);
+            }
+
+            drag.last = pixel;
+        }
+
+        if released {
+            editor.drag = None;
+        }
+    }
+
+    for (entity, rect) in edits {
+        if rect.is_empty() {
+            continue;
+        }
+
+        let (_, mut circuit, handle, _) = match circuit_query.get_mut(entity) {
+            Ok(item) => item,
+            Err(_) => continue,
+        };
+        let circuit = &mut *circuit;
+        let (canvas, simulator) = match (&circuit.canvas, &mut circuit.simulator) {
+            (Some(canvas), Some(simulator)) => (canvas, simulator),
+            _ => continue,
+        };
+        let pixels = canvas.pixels();
+
+        let texture = circuit_materials
+            .get(handle)
+            .and_then(|material| images.get_mut(&material.texture));
+        if let Some(texture) = texture {
+            for y in rect.y..rect.y + rect.height {
+                let start = (y as usize * pixels.width as usize + rect.x as usize) * 4;
+                let end = start + rect.width as usize * 4;
+                texture.data[start..end].copy_from_slice(&pixels.data[start..end]);
+            }
+        }
+
+        simulator.reextract(&pixels.data, pixels.components, rect);
+    }
+
+    finished.sort();
+    finished.dedup();
+    for entity in finished {
+        ev_reload.send(CircuitReloadEvent { entity });
+    }
+}
+
+fn selection_marker_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut color_materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let mesh: Mesh2dHandle = meshes.add(Mesh::from(shape::Quad::new(Vec2::ONE))).into();
+
+    commands.spawn((
+        SelectionMarker,
+        MaterialMesh2dBundle {
+            mesh,
+            material: color_materials.add(ColorMaterial::from(Color::rgba(0.2, 0.6, 1.0, 0.35))),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        },
+    ));
+}
+
+fn selection_marker_system(
+    editor: Res<Editor>,
+    circuit_query: Query<(&CircuitComponent, &Transform), Without<SelectionMarker>>,
+    mut marker_query: Query<(&mut Transform, &mut Visibility), With<SelectionMarker>>,
+) {
+    let selected = editor
+        .selection
+        .filter(|_| editor.editing)
+        .and_then(|(entity, rect)| {
+            let (circuit, transform) = circuit_query.get(entity).ok()?;
+            let simulator = circuit.simulator.as_ref()?;
+            Some((
+                rect,
+                simulator.width,
+                simulator.height,
+                transform.translation,
+            ))
+        });
+
+    for (mut transform, mut visibility) in marker_query.iter_mut() {
+        let (rect, width, height, translation) = match selected {
+            Some(selected) => selected,
+            None => {
+                visibility.is_visible = false;
+                continue;
+            }
+        };
+
+        // Image rows go down while world y goes up.
+        let center = Vec2::new(
+            rect.x as f32 + rect.width as f32 / 2.0 - width as f32 / 2.0,
+            height as f32 / 2.0 - rect.y as f32 - rect.height as f32 / 2.0,
+        );
+        transform.translation = translation + center.extend(2.0);
+        transform.scale = Vec3::new(rect.width as f32, rect.height as f32, 1.0);
+        visibility.is_visible = true;
+    }
+}
@@ -0,0 +1,119 @@
+use bevy::{
+    prelude::*,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+use bls_simulator::Direction;
+
+use crate::{hot_reload::CircuitReloadEvent, CircuitComponent};
+
+/// Arrows are only shown once a pixel covers at least this many screen pixels.
+const MIN_PIXEL_SIZE: f32 = 8.0;
+
+#[derive(Component)]
+struct GateArrow;
+
+#[derive(Component)]
+struct GateArrowsSpawned;
+
+/// Draws the direction of every gate on top of its pixel when zoomed in.
+pub struct GateArrowPlugin;
+
+impl Plugin for GateArrowPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(gate_arrow_spawn_system)
+            .add_system(gate_arrow_reload_system)
+            .add_system(gate_arrow_visibility_system);
+    }
+}
+
+fn gate_arrow_spawn_system(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut color_materials: ResMut<Assets<ColorMaterial>>,
+    circuit_query: Query<(Entity, &CircuitComponent), Without<GateArrowsSpawned>>,
+) {
+    for (entity, circuit) in circuit_query.iter() {
+        let simulator = if let Some(simulator) = &circuit.simulator {
+            simulator
+        } else {
+            continue;
+        };
+
+        let mesh: Mesh2dHandle = meshes
+            .add(Mesh::from(shape::RegularPolygon::new(0.4, 3)))
+            .into();
+        let material = color_materials.add(ColorMaterial::from(Color::rgba(1.0, 0.2, 0.2, 0.8)));
+
+        let half_size = Vec2::new(simulator.width as f32, simulator.height as f32) / 2.0;
+
+        commands
+            .entity(entity)
+            .insert(GateArrowsSpawned)
+            .with_children(|parent| {
+                for gate in &simulator.gates {
+                    // Image rows go down while world y goes up.
+                    let translation = Vec3::new(
+                        gate.x as f32 + 0.5 - half_size.x,
+                        half_size.y - gate.y as f32 - 0.5,
+                        1.0,
+                    );
+
+                    parent.spawn((
+                        GateArrow,
+                        MaterialMesh2dBundle {
+                            mesh: mesh.clone(),
+                            material: material.clone(),
+                            transform: Transform::from_translation(translation)
+                                .with_rotation(direction_rotation(gate.direction)),
+                            visibility: Visibility { is_visible: false },
+                            ..default()
+                        },
+                    ));
+                }
+            });
+    }
+}
+
+/// Gates may have moved, so reloaded circuits get their arrows spawned again.
+fn gate_arrow_reload_system(
+    mut commands: Commands,
+    mut ev_reload: EventReader<CircuitReloadEvent>,
+    arrow_query: Query<(Entity, &Parent), With<GateArrow>>,
+) {
+    for event in ev_reload.iter() {
+        for (arrow, parent) in arrow_query.iter() {
+            if parent.get() == event.entity {
+                commands.entity(arrow).despawn_recursive();
+            }
+        }
+
+        commands.entity(event.entity).remove::<GateArrowsSpawned>();
+    }
+}
+
+fn gate_arrow_visibility_system(
+    camera_query: Query<&Transform, With<Camera>>,
+    mut arrow_query: Query<&mut Visibility, With<GateArrow>>,
+) {
+    let is_visible = camera_query
+        .get_single()
+        .map_or(false, |transform| transform.scale.x <= 1.0 / MIN_PIXEL_SIZE);
+
+    arrow_query.for_each_mut(|mut visibility| {
+        if visibility.is_visible != is_visible {
+            visibility.is_visible = is_visible;
+        }
+    });
+}
+
+/// The triangle mesh points up by default.
+fn direction_rotation(direction: Direction) -> Quat {
+    let angle = match direction {
+        Direction::Up => 0.0,
+        Direction::Left => std::f32::consts::FRAC_PI_2,
+        Direction::Down => std::f32::consts::PI,
+        Direction::Right => -std::f32::consts::FRAC_PI_2,
+    };
+
+    Quat::from_rotation_z(angle)
+}
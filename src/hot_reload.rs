@@ -0,0 +1,137 @@
+use std::{fs, path::Path, time::SystemTime};
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    sprite::Mesh2dHandle,
+};
+use bls_simulator::{load_png_file, Simulator};
+
+use crate::{CircuitComponent, CircuitMaterial, CircuitPlacement, HISTORY_STEPS};
+
+/// How often images are checked for changes, in seconds.
+const POLL_INTERVAL: f32 = 0.5;
+
+/// Sent once the circuit of `entity` was extracted again from its changed image.
+pub struct CircuitReloadEvent {
+    pub entity: Entity,
+}
+
+#[derive(Resource)]
+struct PollTimer(Timer);
+
+/// Rebuilds circuits whose image changed on disk, keeping the state of unchanged wires.
+pub struct HotReloadPlugin;
+
+impl Plugin for HotReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PollTimer(Timer::from_seconds(
+            POLL_INTERVAL,
+            TimerMode::Repeating,
+        )))
+        .add_event::<CircuitReloadEvent>()
+        .add_system(hot_reload_system);
+    }
+}
+
+/// Last modification time of the file, if it can be read.
+pub fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+fn hot_reload_system(
+    time: Res<Time>,
+    mut timer: ResMut<PollTimer>,
+    mut ev_reload: EventWriter<CircuitReloadEvent>,
+    mut placement: ResMut<CircuitPlacement>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut images: ResMut<Assets<Image>>,
+    mut circuit_materials: ResMut<Assets<CircuitMaterial>>,
+    mut circuit_query: Query<(
+        Entity,
+        &mut CircuitComponent,
+        &Handle<CircuitMaterial>,
+        &mut Mesh2dHandle,
+    )>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    for (entity, mut circuit, handle, mut mesh_handle) in circuit_query.iter_mut() {
+        let modified = modified_time(&circuit.path);
+        if modified.is_none() || modified == circuit.modified {
+            continue;
+        }
+
+        let previous = if let Some(previous) = &circuit.simulator {
+            previous
+        } else {
+            // Not loaded yet, the first load reads the new version anyway.
+            continue;
+        };
+
+        // The file may still be being written, try again on the next poll.
+        let pixels = match load_png_file(&circuit.path) {
+            Ok(pixels) => pixels,
+            Err(err) => {
+                warn!("failed to reload {}: {}", circuit.path.display(), err);
+                continue;
+            }
+        };
+
+        let mut simulator =
+            Simulator::from_pixels(pixels.width, pixels.height, pixels.components, &pixels.data)
+                .with_history(HISTORY_STEPS);
+        let carry_over = simulator.carry_over(previous);
+        let resized = simulator.width != previous.width || simulator.height != previous.height;
+
+        let material = if let Some(material) = circuit_materials.get_mut(handle) {
+            material
+        } else {
+            continue;
+        };
+
+        if let Some(image) = images.get_mut(&material.texture) {
+            *image = Image::new(
+                Extent3d {
+                    width: pixels.width,
+                    height: pixels.height,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                pixels.data,
+                TextureFormat::Rgba8UnormSrgb,
+            );
+        }
+
+        // Wires may have been erased, so the overlay is drawn again from scratch.
+        if let Some(overlay_texture) = material.overlay_texture.take() {
+            images.remove(overlay_texture);
+        }
+
+        if resized {
+            let size = Vec2::new(simulator.width as f32, simulator.height as f32);
+            *mesh_handle = meshes.add(Mesh::from(shape::Quad::new(size))).into();
+            placement.changed = true;
+        }
+
+        info!(
+            "reloaded {}, kept the state of {} of {} wires and {} of {} gates",
+            circuit.path.display(),
+            carry_over.wires,
+            simulator.wires.len(),
+            carry_over.gates,
+            simulator.gates.len(),
+        );
+
+        // Edits start over from the new image.
+        circuit.canvas = None;
+
+        circuit.simulator = Some(simulator);
+        circuit.modified = modified;
+        ev_reload.send(CircuitReloadEvent { entity });
+    }
+}
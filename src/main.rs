@@ -1,27 +1,69 @@
+use std::{env, fs, path::PathBuf, time::SystemTime};
+
 use bevy::{
+    asset::FileAssetIo,
     diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
     input::ButtonState,
     math::Vec3Swizzles,
     prelude::*,
     reflect::TypeUuid,
-    render::render_resource::AsBindGroup,
+    render::render_resource::{AsBindGroup, TextureDimension, TextureFormat},
     sprite::{Material2d, Material2dPlugin, MaterialMesh2dBundle, Mesh2dHandle},
+    window::FileDragAndDrop,
 };
 
 mod camera;
-mod simulator;
+mod diagnostic_marker;
+mod editor;
+mod gate_arrow;
+mod hot_reload;
+mod playback;
 
+use bls_simulator::*;
 use camera::{CameraPlugin, WorldClickEvent};
-use simulator::*;
+use diagnostic_marker::DiagnosticMarkerPlugin;
+use editor::{Editor, EditorPlugin};
+use gate_arrow::GateArrowPlugin;
+use hot_reload::{modified_time, HotReloadPlugin};
+use playback::PlaybackPlugin;
+
+/// Circuit opened when no image is given on the command line, in the asset folder.
+const DEFAULT_CIRCUIT_IMAGE: &str = "8bit_cpu.png";
+
+/// Space between circuits opened side by side.
+const CIRCUIT_GAP: f32 = 64.0;
+
+const SAVE_SNAPSHOT_KEY: KeyCode = KeyCode::F5;
+const LOAD_SNAPSHOT_KEY: KeyCode = KeyCode::F9;
+
+/// Steps kept to step back through, half a minute at the default speed and 60 frames per second.
+const HISTORY_STEPS: usize = 20 * 60 * 30;
 
 #[derive(Component)]
 struct CircuitComponent {
+    /// Image file the circuit was loaded from.
+    path: PathBuf,
+    /// Circuits are laid out left to right in the order they were opened.
+    index: usize,
+    /// When the image was last changed, to notice it being edited.
+    modified: Option<SystemTime>,
     simulator: Option<Simulator>,
+    /// Pixels of the texture, once the circuit was edited.
+    canvas: Option<Canvas>,
+    /// The texture could not be read, so there is nothing to simulate.
+    unsupported: bool,
 }
 
 impl Default for CircuitComponent {
     fn default() -> Self {
-        Self { simulator: None }
+        Self {
+            path: PathBuf::new(),
+            index: 0,
+            modified: None,
+            simulator: None,
+            canvas: None,
+            unsupported: false,
+        }
     }
 }
 
@@ -76,61 +118,259 @@ impl Material2d for CircuitMaterial {
     }
 }
 
+/// Images given on the command line.
+#[derive(Resource)]
+struct CircuitPaths(Vec<PathBuf>);
+
+/// Lays circuits out left to right in the order they were opened, as their size
+/// becomes known.
+#[derive(Resource, Default)]
+struct CircuitPlacement {
+    /// Number of circuits opened so far.
+    opened: usize,
+    /// A circuit was loaded or resized since the last layout.
+    changed: bool,
+    right_edge: Option<f32>,
+}
+
+impl CircuitPlacement {
+    /// Index of a newly opened circuit.
+    fn open(&mut self) -> usize {
+        self.opened += 1;
+        self.opened - 1
+    }
+
+    /// Horizontal center of the next circuit, `width` wide.
+    fn place(&mut self, width: f32) -> f32 {
+        let center = match self.right_edge {
+            Some(right_edge) => right_edge + CIRCUIT_GAP + width / 2.0,
+            None => 0.0,
+        };
+        self.right_edge = Some(center + width / 2.0);
+
+        center
+    }
+}
+
 fn main() {
+    let mut paths: Vec<PathBuf> = env::args_os().skip(1).map(PathBuf::from).collect();
+    if paths.is_empty() {
+        // The asset folder is found the way the asset server finds it, not from the
+        // current directory, which other paths are relative to.
+        let asset_folder = AssetPlugin::default().asset_folder;
+        paths.push(
+            FileAssetIo::get_base_path()
+                .join(asset_folder)
+                .join(DEFAULT_CIRCUIT_IMAGE),
+        );
+    }
+
     App::new()
+        .insert_resource(CircuitPaths(paths))
+        .init_resource::<CircuitPlacement>()
         .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
         .add_plugin(FrameTimeDiagnosticsPlugin::default())
         .add_plugin(LogDiagnosticsPlugin::default())
         .add_plugin(CameraPlugin)
+        .add_plugin(GateArrowPlugin)
+        .add_plugin(DiagnosticMarkerPlugin)
+        .add_plugin(PlaybackPlugin)
+        .add_plugin(HotReloadPlugin)
+        .add_plugin(EditorPlugin)
         .add_plugin(Material2dPlugin::<CircuitMaterial>::default())
         .add_startup_system(setup)
         .add_system(circuit_system)
+        .add_system(layout_system)
+        .add_system(file_drop_system)
         .run();
 }
 
 fn setup(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
     mut circuit_materials: ResMut<Assets<CircuitMaterial>>,
     asset_server: Res<AssetServer>,
+    paths: Res<CircuitPaths>,
+    mut placement: ResMut<CircuitPlacement>,
 ) {
-    let texture_handle: Handle<Image> = asset_server.load("8bit_cpu.png");
+    for path in &paths.0 {
+        spawn_circuit(
+            &mut commands,
+            &mut circuit_materials,
+            &asset_server,
+            &mut placement,
+            path.clone(),
+        );
+    }
+}
+
+/// Open PNG files dropped onto the window next to the other circuits.
+fn file_drop_system(
+    mut commands: Commands,
+    mut ev_file_drop: EventReader<FileDragAndDrop>,
+    mut circuit_materials: ResMut<Assets<CircuitMaterial>>,
+    asset_server: Res<AssetServer>,
+    mut placement: ResMut<CircuitPlacement>,
+) {
+    for event in ev_file_drop.iter() {
+        let path = match event {
+            FileDragAndDrop::DroppedFile { path_buf, .. } => path_buf,
+            _ => continue,
+        };
+
+        let is_png = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+        if !is_png {
+            warn!("{} is not a PNG image", path.display());
+            continue;
+        }
+
+        spawn_circuit(
+            &mut commands,
+            &mut circuit_materials,
+            &asset_server,
+            &mut placement,
+            path.clone(),
+        );
+    }
+}
+
+/// Start loading the image at `path` as a new circuit.
+/// Its quad is sized and placed once the image is loaded.
+fn spawn_circuit(
+    commands: &mut Commands,
+    circuit_materials: &mut Assets<CircuitMaterial>,
+    asset_server: &AssetServer,
+    placement: &mut CircuitPlacement,
+    path: PathBuf,
+) {
+    // Asset paths are relative to the asset folder, unless they are absolute, while paths
+    // given by the user are relative to the current directory.
+    let path = match fs::canonicalize(&path) {
+        Ok(path) => path,
+        Err(err) => {
+            error!("failed to open {}: {}", path.display(), err);
+            return;
+        }
+    };
+
+    let texture_handle: Handle<Image> = asset_server.load(path.clone());
 
     commands.spawn(CircuitBundle {
+        circuit: CircuitComponent {
+            modified: modified_time(&path),
+            path,
+            index: placement.open(),
+            ..default()
+        },
         material_mesh: MaterialMesh2dBundle {
-            mesh: meshes
-                .add(Mesh::from(shape::Quad::new(Vec2::new(1024.0, 1024.0))))
-                .into(),
             material: circuit_materials.add(CircuitMaterial {
-                texture: texture_handle.clone(),
+                texture: texture_handle,
                 overlay_texture: None,
             }),
-            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 0.0)),
             ..default()
         },
-        ..default()
     });
 }
 
+/// Layout of the texture formats images are loaded as.
+fn pixel_layout(format: TextureFormat) -> Option<PixelLayout> {
+    let (color, bit_depth) = match format {
+        TextureFormat::R8Unorm => (ColorLayout::Gray, 8),
+        TextureFormat::Rg8Unorm => (ColorLayout::GrayAlpha, 8),
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => (ColorLayout::Rgba, 8),
+        TextureFormat::R16Uint | TextureFormat::R16Unorm => (ColorLayout::Gray, 16),
+        TextureFormat::Rg16Uint | TextureFormat::Rg16Unorm => (ColorLayout::GrayAlpha, 16),
+        TextureFormat::Rgba16Uint | TextureFormat::Rgba16Unorm => (ColorLayout::Rgba, 16),
+        _ => return None,
+    };
+
+    Some(PixelLayout { color, bit_depth })
+}
+
+/// Convert the image to 8-bit RGBA in place, so it is shown and overlaid the way
+/// the simulator sees it, and extract its circuit.
+fn simulator_from_image(image: &mut Image) -> Result<Simulator, String> {
+    let image_size = image.texture_descriptor.size;
+    let format = image.texture_descriptor.format;
+    let layout =
+        pixel_layout(format).ok_or_else(|| format!("unsupported texture format {:?}", format))?;
+
+    let pixels = PixelBuffer::from_raw(image_size.width, image_size.height, layout, &image.data)
+        .map_err(|err| err.to_string())?;
+
+    if layout != PixelLayout::rgba8() {
+        *image = Image::new(
+            image_size,
+            TextureDimension::D2,
+            pixels.data.clone(),
+            TextureFormat::Rgba8UnormSrgb,
+        );
+    }
+
+    Ok(Simulator::from_pixels(
+        pixels.width,
+        pixels.height,
+        pixels.components,
+        &pixels.data,
+    ))
+}
+
 fn circuit_system(
     mut ev_world_click: EventReader<WorldClickEvent>,
-    meshes: ResMut<Assets<Mesh>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    editor: Res<Editor>,
+    mut placement: ResMut<CircuitPlacement>,
+    mut meshes: ResMut<Assets<Mesh>>,
     mut images: ResMut<Assets<Image>>,
     mut circuit_materials: ResMut<Assets<CircuitMaterial>>,
     mut circuit_query: Query<(
         &mut CircuitComponent,
         &Handle<CircuitMaterial>,
         &Transform,
-        &Mesh2dHandle,
+        &mut Mesh2dHandle,
     )>,
 ) {
-    circuit_query.for_each_mut(|(mut circuit, handle, transform, mesh_handle)| {
+    // Every circuit checks every click, clicks draw instead while editing.
+    let clicks: Vec<&WorldClickEvent> = ev_world_click
+        .iter()
+        .filter(|_| !editor.is_editing())
+        .collect();
+
+    circuit_query.for_each_mut(|(mut circuit, handle, transform, mut mesh_handle)| {
         let material = if let Some(material) = circuit_materials.get_mut(handle) {
             material
         } else {
             return;
         };
 
+        if circuit.unsupported {
+            return;
+        }
+
+        if circuit.simulator.is_none() {
+            let image = if let Some(image) = images.get_mut(&material.texture) {
+                image
+            } else {
+                return;
+            };
+
+            match simulator_from_image(image) {
+                Ok(simulator) => {
+                    let size = Vec2::new(simulator.width as f32, simulator.height as f32);
+                    *mesh_handle = meshes.add(Mesh::from(shape::Quad::new(size))).into();
+                    placement.changed = true;
+
+                    circuit.simulator = Some(simulator.with_history(HISTORY_STEPS));
+                }
+                Err(err) => {
+                    error!("failed to load {}: {}", circuit.path.display(), err);
+                    circuit.unsupported = true;
+                    return;
+                }
+            }
+        }
+
         let image = if let Some(image) = images.get(&material.texture) {
             image
         } else {
@@ -143,18 +383,25 @@ fn circuit_system(
             return;
         };
 
-        let simulator = circuit
-            .simulator
-            .get_or_insert_with(|| Simulator::from_image(image));
+        let snapshot_path = snapshot_file_path(&circuit.path);
 
-        let overlay_image_handle = material.overlay_texture.get_or_insert_with(|| {
-            let image = &simulator.raw_image;
+        let simulator = if let Some(simulator) = circuit.simulator.as_mut() {
+            simulator
+        } else {
+            return;
+        };
+
+        let image_size = image.texture_descriptor.size;
+        let image_dimension = image.texture_descriptor.dimension;
+        let image_format = image.texture_descriptor.format;
+        let image_data_len = image.data.len();
 
+        let overlay_image_handle = material.overlay_texture.get_or_insert_with(|| {
             let overlay_image = Image::new(
-                image.texture_descriptor.size,
-                image.texture_descriptor.dimension,
-                vec![255u8; image.data.len()],
-                image.texture_descriptor.format,
+                image_size,
+                image_dimension,
+                vec![255u8; image_data_len],
+                image_format,
             );
 
             images.add(overlay_image)
@@ -166,7 +413,7 @@ fn circuit_system(
             return;
         };
 
-        for event in ev_world_click.iter() {
+        for event in &clicks {
             let world_pos = &event.pos;
 
             let aabb = if let Some(aabb) = mesh.compute_aabb() {
@@ -192,7 +439,19 @@ fn circuit_system(
             simulator.set(x, y, event.state == ButtonState::Pressed);
         }
 
-        simulator.simulate(20);
+        if keyboard_input.just_pressed(SAVE_SNAPSHOT_KEY) {
+            match simulator.snapshot().save(&snapshot_path) {
+                Ok(()) => info!("saved snapshot to {}", snapshot_path.display()),
+                Err(err) => error!("{}: {}", snapshot_path.display(), err),
+            }
+        }
+
+        if keyboard_input.just_pressed(LOAD_SNAPSHOT_KEY) {
+            match Snapshot::load(&snapshot_path).and_then(|snapshot| simulator.restore(&snapshot)) {
+                Ok(()) => info!("restored snapshot from {}", snapshot_path.display()),
+                Err(err) => error!("{}: {}", snapshot_path.display(), err),
+            }
+        }
 
         let components = overlay_image
             .texture_descriptor
@@ -206,14 +465,17 @@ fn circuit_system(
         for y in 0..simulator.height as usize {
             for x in 0..simulator.width as usize {
                 let wire_id = simulator.wire_map[y][x];
+                let pixel = &mut rows[y][x];
 
+                // Edited pixels may no longer be wires.
                 if wire_id == -1 {
+                    pixel[..components - 1]
+                        .iter_mut()
+                        .for_each(|value| *value = 255);
                     continue;
                 }
 
-                let wire_state = simulator.wires[wire_id as usize].state;
-
-                let pixel = &mut rows[y][x];
+                let wire_state = simulator.get_wire_state(wire_id as u32);
 
                 if wire_state {
                     pixel[..components - 1]
@@ -228,3 +490,28 @@ fn circuit_system(
         }
     });
 }
+
+/// Lay the loaded circuits out again after one was loaded or resized, so none overlap.
+fn layout_system(
+    mut placement: ResMut<CircuitPlacement>,
+    mut circuit_query: Query<(&CircuitComponent, &mut Transform)>,
+) {
+    if !placement.changed {
+        return;
+    }
+
+    let mut circuits: Vec<(usize, f32, Mut<Transform>)> = circuit_query
+        .iter_mut()
+        .filter_map(|(circuit, transform)| {
+            let width = circuit.simulator.as_ref()?.width as f32;
+            Some((circuit.index, width, transform))
+        })
+        .collect();
+    circuits.sort_by_key(|&(index, _, _)| index);
+
+    placement.changed = false;
+    placement.right_edge = None;
+    for (_, width, mut transform) in circuits {
+        transform.translation.x = placement.place(width);
+    }
+}
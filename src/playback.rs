@@ -0,0 +1,245 @@
+use bevy::prelude::*;
+use bls_simulator::Simulator;
+
+use crate::{editor::Editor, CircuitComponent};
+
+const PAUSE_KEY: KeyCode = KeyCode::Space;
+const STEP_BACK_KEY: KeyCode = KeyCode::Left;
+const STEP_FORWARD_KEY: KeyCode = KeyCode::Right;
+const LATEST_STEP_KEY: KeyCode = KeyCode::End;
+const FASTER_KEYS: [KeyCode; 2] = [KeyCode::Equals, KeyCode::NumpadAdd];
+const SLOWER_KEYS: [KeyCode; 2] = [KeyCode::Minus, KeyCode::NumpadSubtract];
+const FIXED_TIMESTEP_KEY: KeyCode = KeyCode::T;
+
+const MAX_STEPS_PER_FRAME: u32 = 1 << 12;
+const MIN_STEPS_PER_SECOND: f64 = 0.25;
+const MAX_STEPS_PER_SECOND: f64 = (1 << 16) as f64;
+
+/// How many steps are simulated and when.
+#[derive(Resource)]
+struct Playback {
+    paused: bool,
+    /// Simulate `steps_per_second` steps a second whatever the frame rate,
+    /// instead of `steps_per_frame` steps every frame.
+    fixed_timestep: bool,
+    steps_per_frame: u32,
+    steps_per_second: f64,
+    /// Steps owed by the fixed timestep, less than one unless a frame took too long.
+    accumulated_steps: f64,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            fixed_timestep: false,
+            steps_per_frame: 20,
+            steps_per_second: 60.0,
+            accumulated_steps: 0.0,
+        }
+    }
+}
+
+impl Playback {
+    fn faster(&mut self) {
+        if self.fixed_timestep {
+            self.steps_per_second = (self.steps_per_second * 2.0).min(MAX_STEPS_PER_SECOND);
+        } else {
+            self.steps_per_frame = (self.steps_per_frame * 2).min(MAX_STEPS_PER_FRAME);
+        }
+    }
+
+    fn slower(&mut self) {
+        if self.fixed_timestep {
+            self.steps_per_second = (self.steps_per_second / 2.0).max(MIN_STEPS_PER_SECOND);
+        } else {
+            self.steps_per_frame = (self.steps_per_frame / 2).max(1);
+        }
+    }
+
+    /// Number of steps to simulate in a frame that lasted `delta_seconds`.
+    fn steps(&mut self, delta_seconds: f64) -> u32 {
+        if self.paused {
+            self.accumulated_steps = 0.0;
+            return 0;
+        }
+        if !self.fixed_timestep {
+            return self.steps_per_frame;
+        }
+
+        self.accumulated_steps += delta_seconds * self.steps_per_second;
+        let steps = self
+            .accumulated_steps
+            .floor()
+            .min(MAX_STEPS_PER_FRAME as f64);
+        self.accumulated_steps -= steps;
+
+        // Drop what a slow frame could not catch up with instead of falling further behind.
+        if self.accumulated_steps >= 1.0 {
+            self.accumulated_steps = 0.0;
+        }
+
+        steps as u32
+    }
+
+    fn speed(&self) -> String {
+        if self.fixed_timestep {
+            format!("{} steps/s, fixed timestep", self.steps_per_second)
+        } else {
+            format!("{} steps/frame", self.steps_per_frame)
+        }
+    }
+}
+
+#[derive(Component)]
+struct HudText;
+
+/// Pauses, steps and sets the speed of the circuits, and shows how they are running.
+pub struct PlaybackPlugin;
+
+impl Plugin for PlaybackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Playback>()
+            .add_startup_system(hud_setup)
+            .add_system(playback_system)
+            .add_system(hud_system);
+    }
+}
+
+/// Replay a step undone earlier, or simulate a new one.
+fn advance(simulator: &mut Simulator) {
+    if !simulator.step_forward() {
+        simulator.simulate_one_step();
+    }
+}
+
+fn playback_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    mut playback: ResMut<Playback>,
+    mut circuit_query: Query<&mut CircuitComponent>,
+) {
+    if keyboard_input.just_pressed(PAUSE_KEY) {
+        playback.paused = !playback.paused;
+    }
+    if keyboard_input.just_pressed(FIXED_TIMESTEP_KEY) {
+        playback.fixed_timestep = !playback.fixed_timestep;
+    }
+    if keyboard_input.any_just_pressed(FASTER_KEYS) {
+        playback.faster();
+    }
+    if keyboard_input.any_just_pressed(SLOWER_KEYS) {
+        playback.slower();
+    }
+
+    // Holding shift scrubs a frame worth of steps at a time.
+    let scrub = keyboard_input.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    let (step_back, step_forward, manual_steps) = if scrub {
+        (
+            keyboard_input.pressed(STEP_BACK_KEY),
+            keyboard_input.pressed(STEP_FORWARD_KEY),
+            playback.steps_per_frame,
+        )
+    } else {
+        (
+            keyboard_input.just_pressed(STEP_BACK_KEY),
+            keyboard_input.just_pressed(STEP_FORWARD_KEY),
+            1,
+        )
+    };
+    if step_back || step_forward {
+        playback.paused = true;
+    }
+
+    let steps = playback.steps(time.delta_seconds_f64());
+
+    circuit_query.for_each_mut(|mut circuit| {
+        let simulator = if let Some(simulator) = circuit.simulator.as_mut() {
+            simulator
+        } else {
+            return;
+        };
+
+        if step_back {
+            for _ in 0..manual_steps {
+                simulator.step_back();
+            }
+        }
+        if step_forward {
+            for _ in 0..manual_steps {
+                advance(simulator);
+            }
+        }
+        if keyboard_input.just_pressed(LATEST_STEP_KEY) {
+            while simulator.step_forward() {}
+        }
+
+        if simulator.is_rewound() {
+            for _ in 0..steps {
+                advance(simulator);
+            }
+        } else {
+            simulator.simulate(steps);
+        }
+    });
+}
+
+fn hud_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        HudText,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/DejaVuSansMono.ttf"),
+                font_size: 16.0,
+                color: Color::WHITE,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                left: Val::Px(8.0),
+                top: Val::Px(8.0),
+                ..default()
+            },
+            ..default()
+        }),
+    ));
+}
+
+fn hud_system(
+    playback: Res<Playback>,
+    editor: Res<Editor>,
+    circuit_query: Query<&CircuitComponent>,
+    mut text_query: Query<&mut Text, With<HudText>>,
+) {
+    let state = if playback.paused { "paused" } else { "running" };
+    let mut lines = vec![format!("{}  {}", state, playback.speed())];
+
+    for circuit in circuit_query.iter() {
+        let simulator = if let Some(simulator) = &circuit.simulator {
+            simulator
+        } else {
+            continue;
+        };
+
+        let name = circuit
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy();
+        let mut line = format!("{}  step {}", name, simulator.step_count());
+        if let Some(range) = simulator.history_range() {
+            line += &format!("  history {}..={}", range.start(), range.end());
+        }
+        lines.push(line);
+    }
+
+    if let Some(status) = editor.status() {
+        lines.push(status);
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = lines.join("\n");
+    }
+}